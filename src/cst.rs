@@ -0,0 +1,461 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::{Expr, Update},
+    parser::{Parser, ParserError},
+    source_map::Span,
+    stmt::Stmt,
+    token::Token,
+    token_type::TokenType,
+};
+
+mod tree;
+
+pub use tree::{
+    Builder, Checkpoint, GreenElement, GreenNode, GreenToken, SyntaxElement,
+    SyntaxNode, SyntaxToken, TokenKind,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+    // 파싱을 포기하고 건너뛴 토큰들. 문법은 맞지만 의미가 틀린 곳
+    // (루프 밖 break 등)은 노드가 아니라 errors에만 남는다
+    Error,
+
+    // 선언과 문장
+    Import,
+    Export,
+    VarDecl,
+    FunDecl,
+    ClassDecl,
+    TraitDecl,
+    Method,
+    ClassField,
+    Block,
+    ExprStmt,
+    PrintStmt,
+    IfStmt,
+    WhileStmt,
+    ForStmt,
+    ReturnStmt,
+    BreakStmt,
+    ContinueStmt,
+    ThrowStmt,
+    TryStmt,
+    MatchStmt,
+    Pattern,
+
+    // 식
+    Literal,
+    Name,
+    This,
+    Super,
+    Grouping,
+    List,
+    Map,
+    Lambda,
+    Unary,
+    Binary,
+    Assign,
+    Conditional,
+    Call,
+    Member,
+    Index,
+    Postfix,
+    OptionalChain,
+}
+
+impl NodeKind {
+    // for는 while로 풀려서 나오므로 문장의 첫 토큰으로 구분한다
+    pub(crate) fn of_stmt(stmt: &Stmt, first: TokenType) -> Self {
+        if first == TokenType::For {
+            return NodeKind::ForStmt;
+        }
+        match stmt {
+            Stmt::Block(_) => NodeKind::Block,
+            Stmt::Break(_) => NodeKind::BreakStmt,
+            Stmt::Class(_) => NodeKind::ClassDecl,
+            Stmt::Continue(_) => NodeKind::ContinueStmt,
+            Stmt::Export(_) => NodeKind::Export,
+            Stmt::Expression(_) => NodeKind::ExprStmt,
+            Stmt::Function(_) => NodeKind::FunDecl,
+            Stmt::If(_) => NodeKind::IfStmt,
+            Stmt::Import(_) => NodeKind::Import,
+            Stmt::Match(_) => NodeKind::MatchStmt,
+            Stmt::Print(_) => NodeKind::PrintStmt,
+            Stmt::Return(_) => NodeKind::ReturnStmt,
+            Stmt::Throw(_) => NodeKind::ThrowStmt,
+            Stmt::Trait(_) => NodeKind::TraitDecl,
+            Stmt::Try(_) => NodeKind::TryStmt,
+            Stmt::Var(_) | Stmt::VarPattern(_) => NodeKind::VarDecl,
+            Stmt::While(_) => NodeKind::WhileStmt,
+        }
+    }
+
+    pub(crate) fn of_expr(expr: &Expr) -> Self {
+        match expr {
+            Expr::Assign(_)
+            | Expr::AssignPattern(_)
+            | Expr::CompoundAssign(_)
+            | Expr::Set(_)
+            | Expr::SetIndex(_) => NodeKind::Assign,
+            Expr::Binary(_)
+            | Expr::Coalesce(_)
+            | Expr::Comma(_)
+            | Expr::Logical(_) => NodeKind::Binary,
+            Expr::Call(_) => NodeKind::Call,
+            Expr::Conditional(_) => NodeKind::Conditional,
+            Expr::Get(_) | Expr::OptionalGet(_) => NodeKind::Member,
+            Expr::Grouping(_) => NodeKind::Grouping,
+            Expr::Index(_) => NodeKind::Index,
+            Expr::Lambda(_) => NodeKind::Lambda,
+            Expr::List(_) => NodeKind::List,
+            Expr::Literal(_) => NodeKind::Literal,
+            Expr::Map(_) => NodeKind::Map,
+            Expr::OptionalChain(_) => NodeKind::OptionalChain,
+            Expr::Super(_) => NodeKind::Super,
+            Expr::This(_) => NodeKind::This,
+            Expr::Unary(_) | Expr::Update(Update { prefix: true, .. }) => {
+                NodeKind::Unary
+            }
+            Expr::Update(_) => NodeKind::Postfix,
+            Expr::Variable(_) => NodeKind::Name,
+        }
+    }
+}
+
+pub struct Cst {
+    pub root: SyntaxNode,
+    // Parser가 같이 만든 AST. 에러가 난 문장만 빠지고 (트리에서는 Error
+    // 노드), 블록이나 함수 본문의 나머지 문장은 남는다
+    pub statements: Vec<Stmt>,
+    pub errors: Vec<ParserError>,
+    pub warnings: Vec<ParserError>,
+}
+
+// 주어진 Parser(ASI, 재귀 한도 등 설정 포함)로 파싱하면서 손실 없는 트리를
+// 만든다. parser의 토큰은 source를 스캔한 것이어야 한다
+pub fn parse(source: &str, parser: Parser) -> Cst {
+    let mut parser = parser.with_tree(source);
+    let statements = parser.parse_program();
+    let green = parser.take_tree().expect("parser was given a tree");
+
+    Cst {
+        root: SyntaxNode::new_root(green),
+        statements,
+        errors: parser.take_errors(),
+        warnings: parser.take_warnings(),
+    }
+}
+
+// Parser가 토큰을 하나씩 넘겨주면 그 사이의 공백/주석(문서 주석 포함)을
+// Trivia로 채워 넣는다. 노드는 checkpoint에서 시작해 파싱이 끝난 뒤 감싼다
+pub(crate) struct TreeSink {
+    chars: Vec<char>,
+    // 여기까지의 원문이 트리에 들어갔다
+    offset: usize,
+    builder: Builder,
+}
+
+impl TreeSink {
+    pub(crate) fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            offset: 0,
+            builder: Builder::default(),
+        }
+    }
+
+    pub(crate) fn token(&mut self, token: &Token) {
+        let Span { start, end } = token.span;
+        self.trivia(start);
+        let text: String = self.chars[start..end].iter().collect();
+        self.builder
+            .token(TokenKind::Syntax(token.token_type), &text);
+        self.offset = end;
+    }
+
+    // start에서 시작하는 노드의 checkpoint. 앞의 Trivia는 노드 밖에 둔다
+    pub(crate) fn checkpoint(&mut self, start: usize) -> Checkpoint {
+        self.trivia(start);
+        self.builder.checkpoint()
+    }
+
+    pub(crate) fn wrap(&mut self, checkpoint: Checkpoint, kind: NodeKind) {
+        self.builder.start_node_at(checkpoint, kind);
+        self.builder.finish_node();
+    }
+
+    // Parser가 checkpoint 위치(원문 start)로 되돌아갈 때
+    pub(crate) fn rewind(&mut self, checkpoint: Checkpoint, start: usize) {
+        self.builder.rewind(checkpoint);
+        self.offset = start;
+    }
+
+    pub(crate) fn finish(mut self) -> Rc<GreenNode> {
+        self.trivia(self.chars.len());
+        self.builder.finish(NodeKind::Root)
+    }
+
+    fn trivia(&mut self, end: usize) {
+        if end > self.offset {
+            let text: String = self.chars[self.offset..end].iter().collect();
+            self.builder.token(TokenKind::Trivia, &text);
+            self.offset = end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn cst_with(source: &str, configure: impl FnOnce(Parser) -> Parser) -> Cst {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        parse(source, configure(Parser::new(tokens)))
+    }
+
+    fn cst(source: &str) -> Cst {
+        cst_with(source, |parser| parser)
+    }
+
+    fn kinds(cst: &Cst) -> Vec<NodeKind> {
+        cst.root
+            .child_nodes()
+            .iter()
+            .map(SyntaxNode::kind)
+            .collect()
+    }
+
+    fn messages(cst: &Cst) -> Vec<&str> {
+        cst.errors.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    const PROGRAM: &str = "// 주석\n/// doc\nfun add(a, b = 1, ...rest) {\n  return a + b * 2;\n}\nclass A < B with T {\n  class count = 0;\n  init(x) { this.x = x; }\n  area { return super.area; }\n}\nvar [x, ...xs] = [1, 2, 3];\n{a, b: c} = {\"a\": 1};\nmatch (x) {\n  [1, y] | [y] if y > 0 => print y;\n  _ => { print f(n: 1)?.g ?? -1; }\n}\ntry { throw x; } catch (e) { } finally { }\nfor (var i = 0; i < 3; i++) if (i) continue; else break;\n";
+
+    #[test]
+    fn test_lossless() {
+        let cst = cst(PROGRAM);
+
+        assert!(cst.errors.is_empty(), "{:?}", cst.errors);
+        assert_eq!(cst.root.text(), PROGRAM);
+        assert_eq!(cst.root.span(), Span::new(0, PROGRAM.chars().count()));
+        assert_eq!(
+            kinds(&cst),
+            [
+                NodeKind::FunDecl,
+                NodeKind::ClassDecl,
+                NodeKind::VarDecl,
+                NodeKind::ExprStmt,
+                NodeKind::MatchStmt,
+                NodeKind::TryStmt,
+                NodeKind::ForStmt
+            ]
+        );
+
+        let mut scanner = Scanner::new(PROGRAM.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        let direct = Parser::new(tokens).parse().unwrap();
+        assert_eq!(cst.statements, direct);
+    }
+
+    #[test]
+    fn test_tree_shape() {
+        let cst = cst("a = 1 + 2 * 3;");
+
+        assert_eq!(
+            cst.root.debug_tree(),
+            "Root@0..14
+  ExprStmt@0..14
+    Assign@0..13
+      Name@0..1
+        Syntax(Identifier)@0..1 \"a\"
+      Syntax(Equal)@2..3 \"=\"
+      Binary@4..13
+        Literal@4..5
+          Syntax(Number)@4..5 \"1\"
+        Syntax(Plus)@6..7 \"+\"
+        Binary@8..13
+          Literal@8..9
+            Syntax(Number)@8..9 \"2\"
+          Syntax(Star)@10..11 \"*\"
+          Literal@12..13
+            Syntax(Number)@12..13 \"3\"
+    Syntax(Semicolon)@13..14 \";\"
+"
+        );
+    }
+
+    #[test]
+    fn test_members_and_patterns() {
+        let cst = cst("class A { class n = 1; f() {} g {} }\nvar [a, _] = b;");
+        let kinds: Vec<NodeKind> = cst
+            .root
+            .descendants()
+            .iter()
+            .map(SyntaxNode::kind)
+            .filter(|kind| {
+                matches!(
+                    kind,
+                    NodeKind::Method | NodeKind::ClassField | NodeKind::Pattern
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                NodeKind::ClassField,
+                NodeKind::Method,
+                NodeKind::Method,
+                NodeKind::Pattern,
+                NodeKind::Pattern,
+                NodeKind::Pattern
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_statements() {
+        for source in ["{a: 1};", "{\"a\": 1, 2: \"b\"}[2];"] {
+            let cst = cst(source);
+
+            assert!(cst.errors.is_empty(), "{:?}", cst.errors);
+            assert_eq!(kinds(&cst), [NodeKind::ExprStmt]);
+            assert_eq!(cst.statements.len(), 1);
+            assert!(cst
+                .root
+                .descendants()
+                .iter()
+                .any(|node| node.kind() == NodeKind::Map));
+        }
+    }
+
+    #[test]
+    fn test_doc_comments_are_trivia() {
+        for source in [
+            "{ x; /// t\n}",
+            "class A { /// d\n}",
+            "var x = /// d\n 1;",
+            "/// a\n/// b\nfun f() {}\n/// end",
+        ] {
+            let cst = cst(source);
+
+            assert!(cst.errors.is_empty(), "{}: {:?}", source, cst.errors);
+            assert_eq!(cst.root.text(), source);
+            assert_eq!(cst.statements.len(), 1);
+        }
+
+        let cst = cst("var x = /// d\n 1;");
+        let tokens: Vec<String> = cst.root.child_nodes()[0]
+            .children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Token(token) => Some(token.text().to_string()),
+                SyntaxElement::Node(_) => None,
+            })
+            .collect();
+        assert_eq!(tokens, ["var", " ", "x", " ", "=", " /// d\n ", ";"]);
+    }
+
+    #[test]
+    fn test_automatic_semicolons() {
+        let source =
+            "if (x) print 1\nelse print 2\nvar f = fun () { return 1 }";
+        let cst = cst_with(source, |p| p.with_automatic_semicolons(true));
+
+        assert!(cst.errors.is_empty(), "{:?}", cst.errors);
+        assert_eq!(kinds(&cst), [NodeKind::IfStmt, NodeKind::VarDecl]);
+        assert_eq!(cst.root.text(), source);
+    }
+
+    #[test]
+    fn test_error_nodes_and_recovery() {
+        let source = "fun f() {\n  print (;\n  print 1;\n}\nvar = 2;\nprint 3;";
+        let cst = cst(source);
+
+        assert_eq!(cst.root.text(), source);
+        assert_eq!(
+            messages(&cst),
+            ["Expect expression", "Expect variable name."]
+        );
+        assert_eq!(
+            kinds(&cst),
+            [NodeKind::FunDecl, NodeKind::Error, NodeKind::PrintStmt]
+        );
+
+        // 함수 안의 깨진 문장만 Error 노드가 되고 함수는 남는다
+        let function = &cst.root.child_nodes()[0];
+        let body: Vec<NodeKind> = function
+            .child_nodes()
+            .iter()
+            .map(SyntaxNode::kind)
+            .collect();
+        assert_eq!(body, [NodeKind::Error, NodeKind::PrintStmt]);
+        assert_eq!(function.child_nodes()[0].text(), "print (;");
+
+        assert_eq!(cst.statements.len(), 2);
+        match &cst.statements[0] {
+            Stmt::Function(function) => assert_eq!(function.body.len(), 1),
+            stmt => panic!("expected function, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_pattern_assignment_in_body() {
+        let cst = cst("while (a) { {x, y: z} = p; { print 1; } }");
+
+        assert!(cst.errors.is_empty(), "{:?}", cst.errors);
+        let kinds: Vec<NodeKind> = cst
+            .root
+            .descendants()
+            .iter()
+            .map(SyntaxNode::kind)
+            .filter(|kind| matches!(kind, NodeKind::Pattern | NodeKind::Block))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                NodeKind::Block,
+                NodeKind::Pattern,
+                NodeKind::Pattern,
+                NodeKind::Block
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let sources = [
+            format!("print {}1{};", "(".repeat(500), ")".repeat(500)),
+            format!("{}x;{}", "{".repeat(128), "}".repeat(128)),
+            format!("{}x;{}", "{".repeat(100_000), "}".repeat(100_000)),
+            format!(
+                "var f = {}1{};",
+                "fun () { return ".repeat(100),
+                "; }".repeat(100)
+            ),
+            format!("if (a) x; {}", "else if (a) x; ".repeat(100_000)),
+            format!("{}x;", "while (a) ".repeat(100_000)),
+            format!("print 1{};", "+1".repeat(100_000)),
+            format!("print a{};", ".b".repeat(100_000)),
+        ];
+        for source in &sources {
+            let cst = cst(source);
+
+            assert_eq!(&cst.root.text(), source);
+            assert!(
+                cst.errors[0].message.ends_with("too deeply")
+                    || cst.errors[0].message == "Expression too long",
+                "{:?}",
+                cst.errors[0]
+            );
+        }
+
+        let cst = cst(&format!("{}\nprint 2;", sources[0]));
+        assert_eq!(kinds(&cst), [NodeKind::Error, NodeKind::PrintStmt]);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{fmt, rc::Rc};
+
+use crate::{source_map::Span, token_type::TokenType};
+
+use super::NodeKind;
+
+// 공백과 주석은 Trivia 토큰으로 트리에 남는다 (트리 텍스트 == 원문)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Syntax(TokenType),
+    Trivia,
+}
+
+// green 트리: 위치를 모르는 불변 노드. 같은 모양이면 공유할 수 있다
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+    pub kind: TokenKind,
+    pub text: String,
+    width: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+    pub kind: NodeKind,
+    pub children: Vec<GreenElement>,
+    width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenToken {
+    pub fn new(kind: TokenKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+            width: text.chars().count(),
+        }
+    }
+
+    // 문자 단위 길이
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(GreenElement::width).sum();
+        Self {
+            kind,
+            children,
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.kind == NodeKind::Error
+            || self.children.iter().any(|child| match child {
+                GreenElement::Node(node) => node.has_errors(),
+                GreenElement::Token(_) => false,
+            })
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.write_text(out),
+                GreenElement::Token(token) => out.push_str(&token.text),
+            }
+        }
+    }
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width(),
+            GreenElement::Token(token) => token.width(),
+        }
+    }
+}
+
+// 파서가 green 트리를 아래에서부터 쌓는 도구
+#[derive(Default)]
+pub struct Builder {
+    // 열려 있는 노드들과 그 자식들
+    stack: Vec<(NodeKind, Vec<GreenElement>)>,
+    // 열린 노드가 하나도 없을 때의 자식들 (마지막에 Root가 된다)
+    children: Vec<GreenElement>,
+}
+
+// 나중에 노드로 감쌀 수 있는 위치 (a + b에서 a를 파싱한 뒤 Binary로 감싸기)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checkpoint(usize);
+
+impl Builder {
+    pub fn start_node(&mut self, kind: NodeKind) {
+        self.stack.push((kind, Vec::new()));
+    }
+
+    pub fn finish_node(&mut self) {
+        let (kind, children) = self.stack.pop().expect("no open node");
+        let node = GreenElement::Node(Rc::new(GreenNode::new(kind, children)));
+        self.current().push(node);
+    }
+
+    pub fn token(&mut self, kind: TokenKind, text: &str) {
+        let token = GreenElement::Token(Rc::new(GreenToken::new(kind, text)));
+        self.current().push(token);
+    }
+
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        Checkpoint(self.current().len())
+    }
+
+    // checkpoint 이후에 추가된 자식들을 새 노드로 옮긴다
+    pub fn start_node_at(&mut self, checkpoint: Checkpoint, kind: NodeKind) {
+        let children = self.current().split_off(checkpoint.0);
+        self.stack.push((kind, children));
+    }
+
+    // 되돌아가서 다시 파싱할 때: checkpoint 이후에 추가된 자식들을 버린다
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.current().truncate(checkpoint.0);
+    }
+
+    pub fn finish(mut self, kind: NodeKind) -> Rc<GreenNode> {
+        assert!(self.stack.is_empty(), "unfinished node");
+        Rc::new(GreenNode::new(kind, std::mem::take(&mut self.children)))
+    }
+
+    fn current(&mut self) -> &mut Vec<GreenElement> {
+        match self.stack.last_mut() {
+            Some((_, children)) => children,
+            None => &mut self.children,
+        }
+    }
+}
+
+// red 트리: green 노드에 위치와 부모를 붙인 얇은 핸들. 필요할 때 만든다
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.width())
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.0.green.write_text(&mut text);
+        text
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        let mut children = Vec::new();
+        for child in &self.0.green.children {
+            children.push(match child {
+                GreenElement::Node(green) => {
+                    SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                        green: green.clone(),
+                        offset,
+                        parent: Some(self.clone()),
+                    })))
+                }
+                GreenElement::Token(green) => {
+                    SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        offset,
+                        parent: self.clone(),
+                    })
+                }
+            });
+            offset += child.width();
+        }
+        children
+    }
+
+    pub fn child_nodes(&self) -> Vec<SyntaxNode> {
+        self.children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    // 자기 자신을 포함한 모든 하위 노드 (전위 순회)
+    pub fn descendants(&self) -> Vec<SyntaxNode> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![self.clone()];
+        while let Some(node) = stack.pop() {
+            stack.extend(node.child_nodes().into_iter().rev());
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    // Trivia를 뺀 토큰들 (순서대로)
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => {
+                    if token.kind() != TokenKind::Trivia {
+                        tokens.push(token);
+                    }
+                }
+            }
+        }
+        tokens
+    }
+
+    // 한 줄에 하나씩, 들여쓰기로 트리 모양을 보여준다. Trivia는 생략
+    pub fn debug_tree(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(&mut out, 0);
+        out
+    }
+
+    fn write_tree(&self, out: &mut String, depth: usize) {
+        out.push_str(&format!("{}{:?}\n", "  ".repeat(depth), self));
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => node.write_tree(out, depth + 1),
+                SyntaxElement::Token(token) => {
+                    if token.kind() != TokenKind::Trivia {
+                        let indent = "  ".repeat(depth + 1);
+                        out.push_str(&format!("{}{:?}\n", indent, token));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.0.green.has_errors()
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> TokenKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.width())
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.kind(), span.start, span.end)
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let span = self.span();
+        write!(
+            f,
+            "{:?}@{}..{} {:?}",
+            self.kind(),
+            span.start,
+            span.end,
+            self.text()
+        )
+    }
+}
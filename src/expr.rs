@@ -1,11 +1,14 @@
 // Auto-generated by tools/generate_ast.rs
 // Do not edit manually
 
+use crate::pattern::Pattern;
+use crate::stmt::Stmt;
 use crate::token::Token;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LiteralValue {
     Number(f64),
+    Integer(i64),
     String(String),
     Boolean(bool),
     Nil,
@@ -13,10 +16,44 @@ pub enum LiteralValue {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
+    Assign(Assign),
+    AssignPattern(AssignPattern),
     Binary(Binary),
+    Coalesce(Coalesce),
+    Call(Call),
+    Comma(Comma),
+    CompoundAssign(CompoundAssign),
+    Conditional(Conditional),
+    Get(Get),
     Grouping(Grouping),
+    Index(Index),
+    Lambda(Lambda),
+    List(List),
     Literal(Literal),
+    Logical(Logical),
+    Map(Map),
+    OptionalChain(OptionalChain),
+    OptionalGet(OptionalGet),
+    Set(Set),
+    SetIndex(SetIndex),
+    Super(Super),
+    This(This),
     Unary(Unary),
+    Update(Update),
+    Variable(Variable),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assign {
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignPattern {
+    pub pattern: Pattern,
+    pub equals: Token,
+    pub value: Box<Expr>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,19 +63,145 @@ pub struct Binary {
     pub right: Box<Expr>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coalesce {
+    pub left: Box<Expr>,
+    pub operator: Token,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+    pub names: Vec<Option<Token>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comma {
+    pub left: Box<Expr>,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundAssign {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conditional {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Grouping {
     pub expression: Box<Expr>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lambda {
+    pub keyword: Token,
+    pub params: Vec<Token>,
+    pub defaults: Vec<Option<Expr>>,
+    pub rest: Option<Token>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct List {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Literal {
     pub value: LiteralValue,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Logical {
+    pub left: Box<Expr>,
+    pub operator: Token,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub brace: Token,
+    pub keys: Vec<Expr>,
+    pub values: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionalChain {
+    pub expression: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionalGet {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Set {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetIndex {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Super {
+    pub keyword: Token,
+    pub method: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct This {
+    pub keyword: Token,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Unary {
     pub operator: Token,
     pub right: Box<Expr>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub prefix: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: Token,
+}
+
@@ -0,0 +1,346 @@
+use std::ops::Range;
+
+use crate::{
+    scanner::{ScanError, Scanner},
+    source_map::{FileId, Span},
+    token::Token,
+    token_type::TokenType,
+};
+
+// scanner가 토큰 끝 너머로 볼 수 있는 최대 문자 수 (peek, peek_next)
+const MAX_LOOKAHEAD: usize = 2;
+
+// span(수정 전 기준, 문자 단위)을 text로 바꾸는 편집
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(start: usize, end: usize, text: &str) -> Self {
+        Self {
+            span: Span::new(start, end),
+            text: text.to_string(),
+        }
+    }
+}
+
+// 버퍼 범위를 벗어났거나 start > end인 편집. 버퍼는 그대로 남는다
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditError {
+    pub message: String,
+    pub span: Span,
+}
+
+// 한 번 스캔된 버퍼. 편집이 들어오면 영향받는 구간만 다시 스캔한다
+pub struct TokenBuffer {
+    file: FileId,
+    chars: Vec<char>,
+    tokens: Vec<Token>,
+    errors: Vec<ScanError>,
+}
+
+impl TokenBuffer {
+    pub fn new(source: &str) -> Self {
+        Self::with_file(source, FileId::default())
+    }
+
+    pub fn with_file(source: &str, file: FileId) -> Self {
+        let mut scanner = Scanner::with_file(source.to_string(), file);
+        let tokens = scanner.scan_tokens().clone();
+
+        Self {
+            file,
+            chars: source.chars().collect(),
+            tokens,
+            errors: scanner.errors().to_vec(),
+        }
+    }
+
+    pub fn source(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
+    // 편집을 적용하고 새로 스캔한 토큰의 index 범위를 돌려준다
+    pub fn apply_edit(
+        &mut self,
+        edit: &TextEdit,
+    ) -> Result<Range<usize>, EditError> {
+        let Span { start, end } = edit.span;
+        if start > end || end > self.chars.len() {
+            return Err(EditError {
+                message: format!(
+                    "Edit {}..{} is outside the buffer (length {}).",
+                    start,
+                    end,
+                    self.chars.len()
+                ),
+                span: edit.span,
+            });
+        }
+
+        let inserted: Vec<char> = edit.text.chars().collect();
+        let new_end = start + inserted.len();
+        let delta = inserted.len() as isize - (end - start) as isize;
+        let line_delta =
+            count_newlines(&inserted) - count_newlines(&self.chars[start..end]);
+
+        // Eof 앞까지의 토큰 중, lookahead까지 고려해서 편집에 닿는 첫 토큰
+        let body = self.tokens.len() - 1;
+        let first = self.tokens[..body]
+            .iter()
+            .position(|t| t.span.end + MAX_LOOKAHEAD > start)
+            .unwrap_or(body);
+
+        // 직전 토큰 끝은 scanner가 아무 상태도 갖지 않는 지점
+        let (restart, line) = match first {
+            0 => (0, 1),
+            i => (self.tokens[i - 1].span.end, self.tokens[i - 1].line),
+        };
+
+        self.chars.splice(start..end, inserted);
+        let source: String = self.chars.iter().collect();
+        let mut scanner =
+            Scanner::starting_at(source, self.file, restart, line);
+
+        let mut relexed = Vec::new();
+        let mut old = first;
+        let mut reused = None;
+
+        while let Some(token) = scanner.next_token() {
+            if token.span.start >= new_end {
+                // 편집 뒤에서 예전 토큰과 시작 위치가 맞으면 나머지는 그대로다
+                let old_start = (token.span.start as isize - delta) as usize;
+                while old < body && self.tokens[old].span.start < old_start {
+                    old += 1;
+                }
+                if old < body && self.tokens[old].span.start == old_start {
+                    reused = Some(old);
+                    break;
+                }
+            }
+            relexed.push(token);
+        }
+
+        let relexed_len = relexed.len();
+        let (tail, sync) = match reused {
+            Some(index) => {
+                let tail = self.tokens[index..]
+                    .iter()
+                    .map(|t| shift_token(t, delta, line_delta))
+                    .collect();
+                (tail, self.tokens[index].span.start)
+            }
+            None => {
+                let offset = scanner.offset();
+                let eof = Token {
+                    token_type: TokenType::Eof,
+                    lexeme: String::new(),
+                    literal: None,
+                    line: scanner.line(),
+                    file: self.file,
+                    span: Span::new(offset, offset),
+                };
+                (vec![eof], usize::MAX)
+            }
+        };
+
+        relexed.extend::<Vec<Token>>(tail);
+        self.tokens.splice(first.., relexed);
+
+        // 다시 스캔한 구간의 에러만 새 것으로 바꾼다
+        let mut errors: Vec<ScanError> = self
+            .errors
+            .iter()
+            .filter(|e| e.span.start < restart)
+            .cloned()
+            .collect();
+        errors.extend_from_slice(scanner.errors());
+        errors.extend(
+            self.errors
+                .iter()
+                .filter(|e| e.span.start >= sync)
+                .map(|e| shift_error(e, delta, line_delta)),
+        );
+        self.errors = errors;
+
+        Ok(first..first + relexed_len)
+    }
+}
+
+fn count_newlines(chars: &[char]) -> i32 {
+    chars.iter().filter(|c| **c == '\n').count() as i32
+}
+
+fn shift_span(span: Span, delta: isize) -> Span {
+    Span::new(
+        (span.start as isize + delta) as usize,
+        (span.end as isize + delta) as usize,
+    )
+}
+
+fn shift_token(token: &Token, delta: isize, line_delta: i32) -> Token {
+    Token {
+        span: shift_span(token.span, delta),
+        line: token.line + line_delta,
+        ..token.clone()
+    }
+}
+
+fn shift_error(error: &ScanError, delta: isize, line_delta: i32) -> ScanError {
+    ScanError {
+        span: shift_span(error.span, delta),
+        line: error.line + line_delta,
+        ..error.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "/// Counts down.\nfun count(n) {\n  while (n >= 1.5) {\n    print \"n = \" + n; // show\n    n = n - 1;\n  }\n}\nvar answer = 42;\ncount(answer);\n";
+
+    // 증분 결과가 처음부터 다시 스캔한 결과와 같은지 확인한다
+    fn assert_matches_full(buffer: &TokenBuffer) {
+        let mut scanner = Scanner::new(buffer.source());
+        let tokens = scanner.scan_tokens().clone();
+
+        assert_eq!(
+            buffer.tokens(),
+            &tokens[..],
+            "source: {:?}",
+            buffer.source()
+        );
+        assert_eq!(buffer.errors(), scanner.errors());
+    }
+
+    fn apply(source: &str, edits: &[TextEdit]) -> TokenBuffer {
+        let mut buffer = TokenBuffer::new(source);
+        for edit in edits {
+            buffer.apply_edit(edit).unwrap();
+            assert_matches_full(&buffer);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_edit_inside_identifier() {
+        let buffer = apply("var answer = 42;", &[TextEdit::new(6, 6, "xx")]);
+
+        assert_eq!(buffer.tokens()[1].lexeme, "anxxswer");
+    }
+
+    #[test]
+    fn test_reuses_tokens_after_edit() {
+        let mut buffer = TokenBuffer::new("var a = 1;\nvar b = 2;\nvar c = 3;");
+        let relexed = buffer.apply_edit(&TextEdit::new(8, 9, "100")).unwrap();
+        assert_matches_full(&buffer);
+
+        assert_eq!(relexed, 2..4);
+        assert_eq!(buffer.tokens()[3].lexeme, "100");
+        assert_eq!(buffer.tokens()[5].span, Span::new(13, 16));
+    }
+
+    #[test]
+    fn test_newline_shifts_lines() {
+        let buffer = apply("a;\nb;\nc;", &[TextEdit::new(2, 2, "\n\n")]);
+
+        assert_eq!(buffer.tokens()[2].line, 4);
+        assert_eq!(buffer.tokens()[4].line, 5);
+    }
+
+    #[test]
+    fn test_edit_joins_two_char_token() {
+        let buffer = apply("a = b;", &[TextEdit::new(3, 3, "=")]);
+
+        assert_eq!(buffer.tokens()[1].token_type, TokenType::EqualEqual);
+    }
+
+    #[test]
+    fn test_edit_completes_decimal() {
+        let buffer = apply("1.x", &[TextEdit::new(2, 3, "5")]);
+
+        assert_eq!(buffer.tokens()[0].lexeme, "1.5");
+    }
+
+    #[test]
+    fn test_edit_breaks_comment() {
+        apply("1 // a b\n2", &[TextEdit::new(5, 5, "\n")]);
+    }
+
+    #[test]
+    fn test_open_and_close_string() {
+        apply(
+            "print a; print b;",
+            &[
+                TextEdit::new(6, 6, "\""),
+                TextEdit::new(16, 16, "\""),
+                TextEdit::new(6, 7, ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_delete_everything() {
+        let buffer = apply(PROGRAM, &[TextEdit::new(0, PROGRAM.len(), "")]);
+
+        assert_eq!(buffer.tokens().len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_edit_outside_buffer() {
+        let mut buffer = TokenBuffer::new("var a = 1;");
+        let tokens = buffer.tokens().to_vec();
+
+        for edit in [
+            TextEdit::new(5, 3, "x"),
+            TextEdit::new(8, 11, "x"),
+            TextEdit::new(20, 20, "x"),
+        ] {
+            let error = buffer.apply_edit(&edit).unwrap_err();
+            assert_eq!(error.span, edit.span);
+        }
+        assert_eq!(buffer.source(), "var a = 1;");
+        assert_eq!(buffer.tokens(), &tokens[..]);
+
+        // 끝에 붙이는 편집은 괜찮다
+        buffer.apply_edit(&TextEdit::new(10, 10, " a;")).unwrap();
+        assert_matches_full(&buffer);
+    }
+
+    #[test]
+    fn test_random_edits_match_full_scan() {
+        let fragments = [
+            "", "a", "1", ".", "/", "//", "\"", "\n", " ", "=", "!", "var",
+            "1.2", "@", "é",
+        ];
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % bound as u64) as usize
+        };
+
+        let mut buffer = TokenBuffer::new(PROGRAM);
+        for _ in 0..500 {
+            let len = buffer.source().chars().count();
+            let start = next(len + 1);
+            let end = (start + next(4)).min(len);
+            let text = fragments[next(fragments.len())];
+
+            buffer.apply_edit(&TextEdit::new(start, end, text)).unwrap();
+            assert_matches_full(&buffer);
+        }
+    }
+}
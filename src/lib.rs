@@ -0,0 +1,13 @@
+pub mod cst;
+pub mod expr;
+pub mod incremental;
+pub mod lint;
+pub mod module;
+pub mod parser;
+pub mod pattern;
+pub mod scanner;
+pub mod signature;
+pub mod source_map;
+pub mod stmt;
+pub mod token;
+pub mod token_type;
@@ -0,0 +1,93 @@
+use crate::{
+    parser::{Parser, ParserError},
+    token::Token,
+};
+
+// ASI 모드로 파싱한 뒤, 자동으로 넣은 세미콜론을 source에 직접 써 넣는다.
+// tokens는 source를 스캔한 결과여야 한다 (span이 source의 문자 offset)
+pub fn explicit_semicolons(
+    source: &str,
+    tokens: Vec<Token>,
+) -> Result<String, Vec<ParserError>> {
+    let offsets = inserted_semicolons(tokens)?;
+    Ok(insert_semicolons(source, &offsets))
+}
+
+// 세미콜론이 들어갈 문자 offset들 (오름차순)
+pub fn inserted_semicolons(
+    tokens: Vec<Token>,
+) -> Result<Vec<usize>, Vec<ParserError>> {
+    let mut parser = Parser::new(tokens).with_automatic_semicolons(true);
+    parser.parse()?;
+
+    let mut offsets: Vec<usize> = parser
+        .inserted_semicolons()
+        .iter()
+        .map(|token| token.span.end)
+        .collect();
+    offsets.sort_unstable();
+    Ok(offsets)
+}
+
+// offsets(오름차순, 문자 단위) 앞에 ';'를 넣는다
+pub fn insert_semicolons(source: &str, offsets: &[usize]) -> String {
+    let mut fixed = String::with_capacity(source.len() + offsets.len());
+    let mut next = offsets.iter().peekable();
+    for (offset, c) in source.chars().enumerate() {
+        while next.next_if(|&&at| at == offset).is_some() {
+            fixed.push(';');
+        }
+        fixed.push(c);
+    }
+    for _ in next {
+        fixed.push(';');
+    }
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn fix(source: &str) -> String {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        match explicit_semicolons(source, tokens) {
+            Ok(fixed) => fixed,
+            Err(errors) => panic!("lint failed: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn test_inserts_at_line_ends() {
+        assert_eq!(
+            fix("var a = 1\nprint a // comment\na = a + 1;\n"),
+            "var a = 1;\nprint a; // comment\na = a + 1;\n"
+        );
+    }
+
+    #[test]
+    fn test_inserts_before_brace_and_at_end() {
+        assert_eq!(
+            fix("fun f() { return 1 }\nwhile (x) { break }\nf()"),
+            "fun f() { return 1; }\nwhile (x) { break; }\nf();"
+        );
+    }
+
+    #[test]
+    fn test_expression_continues_across_lines() {
+        assert_eq!(fix("var a = 1 +\n  2\n"), "var a = 1 +\n  2;\n");
+    }
+
+    #[test]
+    fn test_same_line_still_needs_semicolon() {
+        let mut scanner = Scanner::new("var a = 1 var b = 2".to_string());
+        let tokens = scanner.scan_tokens().clone();
+        let errors = explicit_semicolons("var a = 1 var b = 2", tokens)
+            .err()
+            .unwrap();
+
+        assert_eq!(errors[0].message, "Expect ';' after variable declaration.");
+    }
+}
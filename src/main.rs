@@ -6,24 +6,113 @@ use std::{
     process,
 };
 
-mod expr;
-mod parser;
-mod scanner;
-mod token;
-mod token_type;
+use rust_jlox::{
+    lint,
+    module::{ModuleError, ModuleLoader},
+    parser::{Parser, ParserError},
+    scanner::Scanner,
+    source_map::{self, FileId, SourceMap, Span},
+    token::Token,
+    token_type::TokenType,
+};
 
+#[derive(Default)]
 pub struct Jlox {
     had_error: bool,
+    source_map: SourceMap,
+    // --asi: 줄바꿈을 문장 끝으로 받아들인다
+    automatic_semicolons: bool,
 }
 
 impl Jlox {
     pub fn new() -> Self {
-        Self { had_error: false }
+        Self {
+            had_error: false,
+            source_map: SourceMap::new(),
+            automatic_semicolons: false,
+        }
     }
 
     pub fn run_file(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
-        let contents = fs::read_to_string(path)?;
-        self.run(&contents);
+        self.run_files(&[path.to_string()])
+    }
+
+    // 여러 파일과 그 import를 모두 불러온 뒤 의존 순서대로 실행.
+    // import는 불러오는 파일 기준으로 먼저 찾고, 그다음 JLOX_PATH에서 찾는다
+    pub fn run_files(
+        &mut self,
+        paths: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let search_path = env::var_os("JLOX_PATH")
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        let mut loader = ModuleLoader::new(search_path)
+            .with_automatic_semicolons(self.automatic_semicolons);
+        for path in paths {
+            loader.load(&mut self.source_map, path)?;
+        }
+
+        for warning in loader.warnings() {
+            self.warning(warning);
+        }
+        for error in loader.errors() {
+            match error {
+                ModuleError::Scan(e) => self.error(e.file, e.span, &e.message),
+                ModuleError::Parse(e) => {
+                    self.token_error(&e.token, &e.to_string())
+                }
+                ModuleError::Import { token, message } => {
+                    self.token_error(token, message)
+                }
+            }
+        }
+        if !self.had_error {
+            for module in loader.modules() {
+                for stmt in &module.statements {
+                    println!("{:?}", stmt);
+                }
+            }
+        }
+
+        if self.had_error {
+            process::exit(65);
+        }
+        Ok(())
+    }
+
+    // --fix-semicolons: 생략된 세미콜론을 써 넣어 파일을 다시 쓴다
+    pub fn fix_semicolons(
+        &mut self,
+        paths: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        for path in paths {
+            let file = self.source_map.load(path)?;
+            let source = self.source_map.get(file).source.clone();
+            let mut scanner = Scanner::with_file(source, file);
+            let tokens = scanner.scan_tokens().clone();
+            for error in scanner.errors() {
+                self.error(error.file, error.span, &error.message);
+            }
+            if scanner.had_error() {
+                continue;
+            }
+
+            // 정규화(BOM, shebang, CRLF)하기 전 원문에 그대로 넣는다
+            match lint::inserted_semicolons(tokens) {
+                Ok(offsets) => {
+                    let raw = fs::read_to_string(path)?;
+                    let raw_offsets = source_map::raw_offsets(&raw);
+                    let offsets: Vec<usize> =
+                        offsets.iter().map(|&at| raw_offsets[at]).collect();
+                    fs::write(path, lint::insert_semicolons(&raw, &offsets))?;
+                }
+                Err(errors) => {
+                    for error in errors {
+                        self.token_error(&error.token, &error.to_string());
+                    }
+                }
+            }
+        }
 
         if self.had_error {
             process::exit(65);
@@ -43,7 +132,8 @@ impl Jlox {
             match stdin.read_line(&mut line) {
                 Ok(0) => break,
                 Ok(_) => {
-                    self.run(&line);
+                    let id = self.source_map.add("<stdin>", &line);
+                    self.run(id);
                     self.had_error = false;
                 }
                 Err(e) => {
@@ -54,36 +144,102 @@ impl Jlox {
         }
     }
 
-    fn run(&mut self, source: &str) {
-        // 스캐닝, 파싱, 인터프리팅...
-        println!("실행: {}", source);
+    fn run(&mut self, file: FileId) {
+        let source = self.source_map.get(file).source.clone();
+        let mut scanner = Scanner::with_file(source, file);
+        let tokens = scanner.scan_tokens().clone();
+
+        for error in scanner.errors() {
+            self.error(error.file, error.span, &error.message);
+        }
+
+        let mut parser = Parser::new(tokens)
+            .with_automatic_semicolons(self.automatic_semicolons);
+        let result = parser.parse();
+
+        for warning in parser.warnings() {
+            self.warning(warning);
+        }
+
+        match result {
+            Ok(statements) => {
+                for stmt in statements {
+                    println!("{:?}", stmt);
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    self.token_error(&error.token, &error.to_string());
+                }
+            }
+        }
+    }
+
+    fn warning(&self, warning: &ParserError) {
+        let location = self
+            .source_map
+            .location(warning.token.file, warning.token.span);
+        eprintln!(
+            "[{}] Warning at '{}' : {}",
+            location, warning.token.lexeme, warning.message
+        );
     }
 
-    pub fn error(&mut self, line: i32, message: &str) {
-        self.report(line, "", message);
+    pub fn error(&mut self, file: FileId, span: Span, message: &str) {
+        self.report(file, span, "", message);
     }
 
-    pub fn report(&mut self, line: i32, pos: &str, message: &str) {
-        eprintln!("[line {}] Error {} : {}", line, pos, message);
+    pub fn token_error(&mut self, token: &Token, message: &str) {
+        if token.token_type == TokenType::Eof {
+            self.report(token.file, token.span, "at end", message);
+        } else {
+            let pos = format!("at '{}'", token.lexeme);
+            self.report(token.file, token.span, &pos, message);
+        }
+    }
+
+    pub fn report(
+        &mut self,
+        file: FileId,
+        span: Span,
+        pos: &str,
+        message: &str,
+    ) {
+        let location = self.source_map.location(file, span);
+        eprintln!("[{}] Error {} : {}", location, pos, message);
         self.had_error = true;
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
     let mut jlox = Jlox::new();
+    let mut fix_semicolons = false;
+    let mut paths = Vec::new();
 
-    match args.len() {
-        1 => jlox.run_prompt(),
-        2 => {
-            if let Err(e) = jlox.run_file(&args[1]) {
-                eprintln!("Error: {}", e);
-                process::exit(1);
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--asi" => jlox.automatic_semicolons = true,
+            "--fix-semicolons" => fix_semicolons = true,
+            _ if arg.starts_with("--") => {
+                eprintln!(
+                    "사용법: jaylox [--asi] [--fix-semicolons] [scripts]"
+                );
+                process::exit(64);
             }
+            _ => paths.push(arg),
         }
-        _ => {
-            eprintln!("사용법: jaylox [scripts]");
-            process::exit(64);
+    }
+
+    let result = match (fix_semicolons, paths.is_empty()) {
+        (true, _) => jlox.fix_semicolons(&paths),
+        (false, true) => {
+            jlox.run_prompt();
+            Ok(())
         }
+        (false, false) => jlox.run_files(&paths),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
     }
 }
@@ -0,0 +1,336 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    parser::{Parser, ParserError},
+    scanner::{ScanError, Scanner},
+    source_map::{FileId, SourceMap},
+    stmt::Stmt,
+    token::Token,
+};
+
+// 파싱까지 끝난 모듈 하나. imports는 (별칭, modules() 안의 index)
+#[derive(Debug)]
+pub struct Module {
+    pub file: FileId,
+    pub path: PathBuf,
+    pub statements: Vec<Stmt>,
+    pub imports: Vec<(String, usize)>,
+}
+
+#[derive(Debug)]
+pub enum ModuleError {
+    Scan(ScanError),
+    Parse(ParserError),
+    // 찾을 수 없는 모듈, 순환 import 등. token은 import 문의 경로
+    Import { token: Token, message: String },
+}
+
+// import를 따라가며 파일을 한 번씩만 읽고 파싱한다.
+// modules()는 의존하는 모듈이 먼저 오는 순서라 그대로 실행하면 된다
+#[derive(Default)]
+pub struct ModuleLoader {
+    search_path: Vec<PathBuf>,
+    automatic_semicolons: bool,
+    modules: Vec<Module>,
+    // canonical 경로 -> modules index
+    cache: HashMap<PathBuf, usize>,
+    // 지금 불러오는 중인 (canonical 경로, 표시용 이름)
+    loading: Vec<(PathBuf, String)>,
+    errors: Vec<ModuleError>,
+    warnings: Vec<ParserError>,
+}
+
+impl ModuleLoader {
+    pub fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_automatic_semicolons(mut self, enabled: bool) -> Self {
+        self.automatic_semicolons = enabled;
+        self
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn errors(&self) -> &[ModuleError] {
+        &self.errors
+    }
+
+    pub fn warnings(&self) -> &[ParserError] {
+        &self.warnings
+    }
+
+    // 진입 파일을 불러온다. 읽을 수 없는 진입 파일만 io 에러로 돌려준다
+    pub fn load(
+        &mut self,
+        source_map: &mut SourceMap,
+        path: &str,
+    ) -> io::Result<usize> {
+        let canonical = fs::canonicalize(path)?;
+        if let Some(&index) = self.cache.get(&canonical) {
+            return Ok(index);
+        }
+        let file = source_map.load(path)?;
+        Ok(self.load_file(source_map, file, canonical))
+    }
+
+    fn load_file(
+        &mut self,
+        source_map: &mut SourceMap,
+        file: FileId,
+        canonical: PathBuf,
+    ) -> usize {
+        let source = source_map.get(file).source.clone();
+        let name = source_map.get(file).name.clone();
+        let mut scanner = Scanner::with_file(source, file);
+        let tokens = scanner.scan_tokens().clone();
+        self.errors
+            .extend(scanner.errors().iter().cloned().map(ModuleError::Scan));
+
+        let mut parser = Parser::new(tokens)
+            .with_automatic_semicolons(self.automatic_semicolons);
+        let result = parser.parse();
+        self.warnings.extend(parser.take_warnings());
+        let statements = match result {
+            Ok(statements) => statements,
+            Err(errors) => {
+                self.errors
+                    .extend(errors.into_iter().map(ModuleError::Parse));
+                Vec::new()
+            }
+        };
+
+        self.loading.push((canonical.clone(), name));
+        let mut imports = Vec::new();
+        for stmt in &statements {
+            if let Stmt::Import(import) = stmt {
+                if let Some(index) = self.import(source_map, &import.path) {
+                    imports.push((import.alias.lexeme.clone(), index));
+                }
+            }
+        }
+        self.loading.pop();
+
+        self.modules.push(Module {
+            file,
+            path: canonical.clone(),
+            statements,
+            imports,
+        });
+        let index = self.modules.len() - 1;
+        self.cache.insert(canonical, index);
+        index
+    }
+
+    fn import(
+        &mut self,
+        source_map: &mut SourceMap,
+        token: &Token,
+    ) -> Option<usize> {
+        let requested = token.literal.clone().unwrap_or_default();
+        let Some(path) = self.resolve(&requested) else {
+            self.import_error(
+                token,
+                format!("Cannot find module '{}'.", requested),
+            );
+            return None;
+        };
+        let canonical = match fs::canonicalize(&path) {
+            Ok(canonical) => canonical,
+            Err(error) => {
+                self.import_error(
+                    token,
+                    format!("Cannot read module '{}': {}", requested, error),
+                );
+                return None;
+            }
+        };
+
+        if let Some(start) =
+            self.loading.iter().position(|(p, _)| *p == canonical)
+        {
+            let mut chain: Vec<&str> = self.loading[start..]
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect();
+            chain.push(&self.loading[start].1);
+            let message = format!("Cyclic import: {}.", chain.join(" -> "));
+            self.import_error(token, message);
+            return None;
+        }
+        if let Some(&index) = self.cache.get(&canonical) {
+            return Some(index);
+        }
+
+        match source_map.load(&path.to_string_lossy()) {
+            Ok(file) => Some(self.load_file(source_map, file, canonical)),
+            Err(error) => {
+                self.import_error(
+                    token,
+                    format!("Cannot read module '{}': {}", requested, error),
+                );
+                None
+            }
+        }
+    }
+
+    // 불러오는 파일의 디렉터리를 먼저, 그다음 search path를 순서대로 본다
+    fn resolve(&self, requested: &str) -> Option<PathBuf> {
+        let importer = self.loading.last().map(|(path, _)| path.as_path());
+        importer
+            .and_then(Path::parent)
+            .into_iter()
+            .chain(self.search_path.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(requested))
+            .find(|path| path.is_file())
+    }
+
+    fn import_error(&mut self, token: &Token, message: String) {
+        self.errors.push(ModuleError::Import {
+            token: token.clone(),
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 테스트마다 따로 쓰는 임시 디렉터리. 테스트가 끝나면 지운다
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn join(&self, path: &str) -> PathBuf {
+            self.0.join(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write_files(test: &str, files: &[(&str, &str)]) -> TempDir {
+        let dir = std::env::temp_dir().join(format!(
+            "jlox-module-{}-{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, contents) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        TempDir(fs::canonicalize(dir).unwrap())
+    }
+
+    fn messages(loader: &ModuleLoader) -> Vec<String> {
+        loader
+            .errors()
+            .iter()
+            .map(|error| match error {
+                ModuleError::Scan(e) => e.message.clone(),
+                ModuleError::Parse(e) => e.message.clone(),
+                ModuleError::Import { message, .. } => message.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_loads_dependencies_once_in_order() {
+        let dir = write_files(
+            "order",
+            &[
+                (
+                    "main.lox",
+                    "import \"a.lox\" as a;\nimport \"lib/b.lox\" as b;",
+                ),
+                ("a.lox", "import \"lib/b.lox\" as b;\nexport var x = 1;"),
+                ("lib/b.lox", "export fun f() {}"),
+            ],
+        );
+        let mut map = SourceMap::new();
+        let mut loader = ModuleLoader::new(Vec::new());
+        let main = loader
+            .load(&mut map, &dir.join("main.lox").to_string_lossy())
+            .unwrap();
+
+        assert!(loader.errors().is_empty(), "{:?}", loader.errors());
+        let modules = loader.modules();
+        assert_eq!(modules.len(), 3);
+        assert!(modules[0].path.ends_with("lib/b.lox"));
+        assert!(modules[1].path.ends_with("a.lox"));
+        assert_eq!(main, 2);
+        assert_eq!(
+            modules[main].imports,
+            vec![("a".to_string(), 1), ("b".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_search_path() {
+        let dir = write_files(
+            "search",
+            &[
+                ("app/main.lox", "import \"util.lox\" as util;"),
+                ("std/util.lox", "export var pi = 3.14;"),
+            ],
+        );
+        let mut map = SourceMap::new();
+        let mut loader = ModuleLoader::new(vec![dir.join("std")]);
+        loader
+            .load(&mut map, &dir.join("app/main.lox").to_string_lossy())
+            .unwrap();
+
+        assert!(loader.errors().is_empty(), "{:?}", loader.errors());
+        assert_eq!(loader.modules().len(), 2);
+    }
+
+    #[test]
+    fn test_missing_module() {
+        let dir =
+            write_files("missing", &[("main.lox", "import \"x.lox\" as x;")]);
+        let mut map = SourceMap::new();
+        let mut loader = ModuleLoader::new(Vec::new());
+        loader
+            .load(&mut map, &dir.join("main.lox").to_string_lossy())
+            .unwrap();
+
+        assert_eq!(messages(&loader), ["Cannot find module 'x.lox'."]);
+    }
+
+    #[test]
+    fn test_cyclic_import_shows_chain() {
+        let dir = write_files(
+            "cycle",
+            &[
+                ("a.lox", "import \"b.lox\" as b;"),
+                ("b.lox", "import \"c.lox\" as c;"),
+                ("c.lox", "import \"a.lox\" as a;"),
+            ],
+        );
+        let a = dir.join("a.lox").to_string_lossy().to_string();
+        let b = dir.join("b.lox").to_string_lossy().to_string();
+        let c = dir.join("c.lox").to_string_lossy().to_string();
+        let mut map = SourceMap::new();
+        let mut loader = ModuleLoader::new(Vec::new());
+        loader.load(&mut map, &a).unwrap();
+
+        assert_eq!(
+            messages(&loader),
+            [format!("Cyclic import: {} -> {} -> {} -> {}.", a, b, c, a)]
+        );
+    }
+}
@@ -1,18 +1,109 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use rules::{rule, Associativity, Precedence};
+
 use crate::{
-    expr::{Binary, Expr, Grouping, Literal, LiteralValue, Unary},
+    cst::{Checkpoint, GreenNode, NodeKind, TreeSink},
+    expr::{
+        Assign, AssignPattern, Binary, Call, Coalesce, Comma, CompoundAssign,
+        Conditional, Expr, Get, Grouping, Index, Lambda, List, Literal,
+        LiteralValue, Logical, Map, OptionalChain, OptionalGet, Set, SetIndex,
+        Super, This, Unary, Update, Variable,
+    },
+    pattern::{self, Pattern},
+    stmt::{
+        Block, Break, Class, Continue, Export, Expression, Function, If,
+        Import, Match, Print, Return, Stmt, Throw, Trait, Try, Var, VarPattern,
+        While,
+    },
     token::Token,
     token_type::TokenType,
 };
 
+pub(crate) mod rules;
+
+const MAX_ARGUMENTS: usize = 255;
+// 식/문장/패턴을 합쳐 이 깊이까지 재귀한다. 더 깊으면 스택이 넘치기 전에 에러.
+// debug 빌드에서 2MB 스레드 스택(테스트 스레드 기본값)에 들어가는 값.
+// 문법상 한 단계(식, 문장, 블록, 패턴)가 한 칸이다: `if (a) {`는 if 문과 블록으로
+// 두 칸, `fun () { return ... }` 람다는 식/본문/return 세 칸.
+const DEFAULT_MAX_DEPTH: usize = 128;
+// 식 트리 높이 한도. a + b + c, a.f().g() 처럼 왼쪽으로 쌓이는 식은 파싱에
+// 재귀가 없어 위 한도에 걸리지 않지만, 트리를 Drop/Debug/비교할 때는 높이만큼
+// 재귀한다. 2MB 스택의 debug 빌드에서 Debug 출력이 높이 약 2400까지 된다
+const MAX_EXPRESSION_HEIGHT: usize = 1024;
+
+// for (initializer; condition; increment)
+struct ForClauses {
+    initializer: Option<Stmt>,
+    condition: Option<Expr>,
+    increment: Option<Expr>,
+}
+
+impl ForClauses {
+    // increment는 while에 따로 두어 continue 후에도 실행되게 한다
+    fn desugar(self, body: Stmt) -> Stmt {
+        let condition = self.condition.unwrap_or(Expr::Literal(Literal {
+            value: LiteralValue::Boolean(true),
+        }));
+        let body = Stmt::While(While {
+            condition,
+            body: Box::new(body),
+            increment: self.increment,
+        });
+
+        match self.initializer {
+            Some(initializer) => Stmt::Block(Block {
+                statements: vec![initializer, body],
+            }),
+            None => body,
+        }
+    }
+}
+
+// 함수 선언과 람다가 공유하는 매개변수 목록 (defaults는 names와 같은 길이)
+#[derive(Default)]
+struct Parameters {
+    names: Vec<Token>,
+    defaults: Vec<Option<Expr>>,
+    rest: Option<Token>,
+}
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // 토큰 index -> 그 토큰 바로 앞에 붙은 문서 주석
+    docs: HashMap<usize, String>,
+    // 파싱을 멈추지 않고 계속 진행한 에러들
+    errors: Vec<ParserError>,
+    // 현재 위치에서 check해 본 (= 받을 수 있었던) 토큰 종류
+    expected: Vec<TokenType>,
+    // break/continue 검사용. 함수 본문에 들어가면 0부터 다시 센다
+    loop_depth: usize,
+    // import/export는 블록 밖(파일 최상위)에서만 쓸 수 있다
+    block_depth: usize,
+    // 줄바꿈을 문장 끝으로 받아들이는 모드와, 그렇게 넣은 세미콜론 바로 앞 토큰
+    automatic_semicolons: bool,
+    inserted_semicolons: Vec<Token>,
+    // 지금 재귀 깊이와 그 한도
+    depth: usize,
+    max_depth: usize,
+    // 방금 끝난 식(들) 중 가장 높은 식 트리의 높이
+    height: usize,
+    // 지금까지 선언된 trait 이름 -> 메서드 이름 (with 충돌 검사용)
+    traits: HashMap<String, Vec<String>>,
+    // 에러는 아니지만 알려줄 것 (도달할 수 없는 match arm 등)
+    warnings: Vec<ParserError>,
+    // cst::parse에서만 만드는 손실 없는 트리
+    tree: Option<TreeSink>,
 }
 
+#[derive(Debug)]
 pub struct ParserError {
     pub message: String,
     pub line: usize,
-    pub token: Token,
+    pub token: Box<Token>,
+    pub expected: Vec<TokenType>,
 }
 
 impl ParserError {
@@ -20,213 +111,3188 @@ impl ParserError {
         Self {
             message: message.to_string(),
             line,
-            token,
+            token: Box::new(token),
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn with_expected(mut self, expected: Vec<TokenType>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    // "expected `)` or `,`, found `;`"
+    pub fn expected_found(&self) -> Option<String> {
+        let names: Vec<String> =
+            self.expected.iter().map(|t| t.to_string()).collect();
+        let expected = match names.as_slice() {
+            [] => return None,
+            [one] => one.clone(),
+            [rest @ .., last] if rest.len() == 1 => {
+                format!("{} or {}", rest[0], last)
+            }
+            [rest @ .., last] => {
+                format!("one of {} or {}", rest.join(", "), last)
+            }
+        };
+
+        let found = match self.token.token_type {
+            TokenType::Eof => "end of file".to_string(),
+            _ => format!("`{}`", self.token.lexeme),
+        };
+        Some(format!("expected {}, found {}", expected, found))
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.expected_found() {
+            Some(expected) => write!(f, "{} ({})", self.message, expected),
+            None => write!(f, "{}", self.message),
         }
     }
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        // 문서 주석은 토큰 흐름에서 빼고 다음 토큰 위치에 기록해 둔다
+        let mut docs: HashMap<usize, String> = HashMap::new();
+        let mut rest = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token.token_type == TokenType::DocComment {
+                let text = token.literal.unwrap_or_default();
+                docs.entry(rest.len())
+                    .and_modify(|doc| {
+                        doc.push('\n');
+                        doc.push_str(&text);
+                    })
+                    .or_insert(text);
+            } else {
+                rest.push(token);
+            }
+        }
+
+        Self {
+            tokens: rest,
+            current: 0,
+            docs,
+            errors: Vec::new(),
+            expected: Vec::new(),
+            loop_depth: 0,
+            block_depth: 0,
+            automatic_semicolons: false,
+            inserted_semicolons: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            height: 0,
+            traits: HashMap::new(),
+            warnings: Vec::new(),
+            tree: None,
+        }
     }
 
-    fn expression(&mut self) -> Result<Expr, ParserError> {
-        self.equality()
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParserError>> {
+        let statements = self.parse_program();
+
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
-    fn equality(&mut self) -> Result<Expr, ParserError> {
-        let mut expr: Expr = self.comparison()?;
+    // 에러가 난 최상위 선언만 빼고 돌려준다. 에러는 self.errors에 쌓인다
+    pub(crate) fn parse_program(&mut self) -> Vec<Stmt> {
+        let mut statements = Vec::new();
 
-        while self.match_tokens(&[TokenType::BangEqual, TokenType::EqualEqual])
-        {
-            let operator = self.previous();
-            let right = self.comparison()?;
+        while !self.is_at_end() {
+            let checkpoint = self.checkpoint();
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                    self.wrap(checkpoint, NodeKind::Error);
+                }
+            }
+        }
+        statements
+    }
 
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_automatic_semicolons(mut self, enabled: bool) -> Self {
+        self.automatic_semicolons = enabled;
+        self
+    }
+
+    // source는 토큰을 스캔한 원문. 파싱하면서 CST도 만든다
+    pub(crate) fn with_tree(mut self, source: &str) -> Self {
+        self.tree = Some(TreeSink::new(source));
+        self
+    }
+
+    pub(crate) fn take_tree(&mut self) -> Option<Rc<GreenNode>> {
+        self.tree.take().map(TreeSink::finish)
+    }
+
+    pub(crate) fn take_errors(&mut self) -> Vec<ParserError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // ASI 모드에서 세미콜론을 넣은 자리 (각 토큰 바로 뒤)
+    pub fn inserted_semicolons(&self) -> &[Token] {
+        &self.inserted_semicolons
+    }
+
+    pub fn warnings(&self) -> &[ParserError] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<ParserError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn parse_expression(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.expression()?;
+        if self.errors.is_empty() {
+            Ok(expr)
+        } else {
+            Err(self.errors.remove(0))
         }
+    }
 
-        Ok(expr)
+    fn declaration(&mut self) -> Result<Stmt, ParserError> {
+        let checkpoint = self.checkpoint();
+        let first = self.peek().token_type;
+        let stmt = self.nested("Statement", Self::declaration_inner)?;
+        self.wrap(checkpoint, NodeKind::of_stmt(&stmt, first));
+        Ok(stmt)
     }
 
-    fn comparison(&mut self) -> Result<Expr, ParserError> {
-        let mut expr: Expr = self.term()?;
+    fn declaration_inner(&mut self) -> Result<Stmt, ParserError> {
+        let doc = self.docs.get(&self.current).cloned();
 
-        while self.match_tokens(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
-            let operator: Token = self.previous();
-            let right: Expr = self.term()?;
+        if self.match_tokens(&[TokenType::Import]) {
+            return self.import_declaration();
+        }
+        if self.match_tokens(&[TokenType::Export]) {
+            return self.export_declaration(doc);
+        }
+        self.plain_declaration(doc)
+    }
 
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+    fn plain_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        if self.match_tokens(&[TokenType::Class]) {
+            return self.class_declaration(doc);
         }
-        Ok(expr)
+        if self.match_tokens(&[TokenType::Trait]) {
+            return self.trait_declaration(doc);
+        }
+        // `fun (` 로 시작하면 익명 함수 식이므로 식 문장으로 넘긴다
+        if self.check(TokenType::Fun)
+            && self.check_ahead(1, TokenType::Identifier)
+        {
+            self.advance();
+            return Ok(Stmt::Function(self.function("function", doc)?));
+        }
+        if self.match_tokens(&[TokenType::Var]) {
+            if self.check(TokenType::LeftBracket)
+                || self.check(TokenType::LeftBrace)
+            {
+                return self.var_pattern_declaration(doc);
+            }
+            return self.var_declaration(doc);
+        }
+        // 이미 이 문장 몫으로 한 단계를 셌다
+        self.statement_inner()
     }
 
-    fn term(&mut self) -> Result<Expr, ParserError> {
-        let mut expr: Expr = self.factor()?;
+    // import "path/to/mod.lox" as m;
+    fn import_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        if self.block_depth > 0 {
+            self.errors.push(
+                self.error(&keyword, "Can only import at the top level."),
+            );
+        }
+
+        let path =
+            self.consume(TokenType::String, "Expect module path string.")?;
+        self.consume(TokenType::As, "Expect 'as' after module path.")?;
+        let alias =
+            self.consume(TokenType::Identifier, "Expect module name.")?;
+        self.end_statement("Expect ';' after import.")?;
 
-        while self.match_tokens(&[TokenType::Plus, TokenType::Minus]) {
-            let operator: Token = self.previous();
-            let right: Expr = self.factor()?;
+        Ok(Stmt::Import(Import {
+            keyword,
+            path,
+            alias,
+        }))
+    }
 
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            })
+    // export var / fun / class
+    fn export_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        if self.block_depth > 0 {
+            self.errors.push(
+                self.error(&keyword, "Can only export at the top level."),
+            );
         }
-        Ok(expr)
+
+        // 기대 토큰을 모두 기록하도록 |로 묶는다
+        let is_declaration = self.check(TokenType::Var)
+            | self.check(TokenType::Fun)
+            | self.check(TokenType::Class)
+            | self.check(TokenType::Trait);
+        if !is_declaration || self.check_ahead(1, TokenType::LeftParen) {
+            return Err(
+                self.error(self.peek(), "Expect declaration after 'export'.")
+            );
+        }
+        let checkpoint = self.checkpoint();
+        let first = self.peek().token_type;
+        let declaration = self.plain_declaration(doc)?;
+        self.wrap(checkpoint, NodeKind::of_stmt(&declaration, first));
+
+        Ok(Stmt::Export(Export {
+            keyword,
+            declaration: Box::new(declaration),
+        }))
     }
 
-    fn factor(&mut self) -> Result<Expr, ParserError> {
-        let mut expr: Expr = self.unary()?;
+    fn class_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokenType::Identifier, "Expect class name.")?;
+
+        let mut superclass = None;
+        if self.match_tokens(&[TokenType::Less]) {
+            let name =
+                self.consume(TokenType::Identifier, "Expect superclass name.")?;
+            superclass = Some(Expr::Variable(Variable { name }));
+        }
+
+        let mut traits = Vec::new();
+        if self.match_tokens(&[TokenType::With]) {
+            loop {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect trait name.")?;
+                traits.push(Expr::Variable(Variable { name }));
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
 
-        while self.match_tokens(&[TokenType::Slash, TokenType::Star]) {
-            let operator: Token = self.previous();
-            let right: Expr = self.unary()?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
 
-            expr = Expr::Binary(Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            })
+        let mut class = Class {
+            name,
+            superclass,
+            traits,
+            methods: Vec::new(),
+            class_methods: Vec::new(),
+            getters: Vec::new(),
+            static_fields: Vec::new(),
+            static_values: Vec::new(),
+            doc,
+        };
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let checkpoint = self.checkpoint();
+            let fields = class.static_fields.len();
+            self.class_member(&mut class)?;
+            let kind = if class.static_fields.len() > fields {
+                NodeKind::ClassField
+            } else {
+                NodeKind::Method
+            };
+            self.wrap(checkpoint, kind);
         }
-        Ok(expr)
+
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+        self.check_trait_conflicts(&class);
+
+        Ok(Stmt::Class(class))
     }
 
-    fn unary(&mut self) -> Result<Expr, ParserError> {
-        if self.match_tokens(&[TokenType::Bang, TokenType::Minus]) {
-            let operator: Token = self.previous();
-            let right: Expr = self.unary()?;
+    // trait T { method() {} ... }
+    // trait 메서드 안의 super는 trait를 가져다 쓴 클래스의 superclass를 가리킨다
+    fn trait_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokenType::Identifier, "Expect trait name.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before trait body.")?;
 
-            return Ok(Expr::Unary(Unary {
-                operator,
-                right: Box::new(right),
-            }));
+        let mut methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let checkpoint = self.checkpoint();
+            let doc = self.docs.get(&self.current).cloned();
+            methods.push(self.function("method", doc)?);
+            self.wrap(checkpoint, NodeKind::Method);
         }
-        self.primary()
+        self.consume(TokenType::RightBrace, "Expect '}' after trait body.")?;
+
+        self.traits.insert(
+            name.lexeme.clone(),
+            methods.iter().map(|m| m.name.lexeme.clone()).collect(),
+        );
+        Ok(Stmt::Trait(Trait { name, methods, doc }))
     }
 
-    fn primary(&mut self) -> Result<Expr, ParserError> {
-        let token = self.peek();
+    // 두 trait 이상이 같은 이름의 메서드를 주는데 클래스가 같은 이름의
+    // 멤버를 직접 정의하지 않았으면 에러. with 목록에 같은 trait가 두 번
+    // 나와도 에러. 이 파일에서 먼저 선언된 trait만 검사할 수 있다
+    fn check_trait_conflicts(&mut self, class: &Class) {
+        let members: Vec<&str> = class
+            .methods
+            .iter()
+            .chain(&class.getters)
+            .chain(&class.class_methods)
+            .map(|f| f.name.lexeme.as_str())
+            .chain(class.static_fields.iter().map(|t| t.lexeme.as_str()))
+            .collect();
 
-        match token.token_type {
-            TokenType::False => {
-                self.advance();
-                Ok(Expr::Literal(Literal {
-                    value: LiteralValue::Boolean(false),
-                }))
+        let mut seen: Vec<&str> = Vec::new();
+        let mut providers: Vec<(&str, Vec<&str>)> = Vec::new();
+        for expr in &class.traits {
+            let Expr::Variable(variable) = expr else {
+                continue;
+            };
+            let trait_name = variable.name.lexeme.as_str();
+            if seen.contains(&trait_name) {
+                let message = format!(
+                    "Duplicate trait '{}' in class '{}'.",
+                    trait_name, class.name.lexeme
+                );
+                self.errors.push(self.error(&variable.name, &message));
+                continue;
             }
+            seen.push(trait_name);
 
-            TokenType::True => {
-                self.advance();
-                Ok(Expr::Literal(Literal {
-                    value: LiteralValue::Boolean(true),
-                }))
+            let Some(methods) = self.traits.get(trait_name) else {
+                continue;
+            };
+            for method in methods {
+                if members.contains(&method.as_str()) {
+                    continue;
+                }
+                match providers.iter_mut().find(|(name, _)| name == method) {
+                    Some((_, traits)) => traits.push(trait_name),
+                    None => providers.push((method, vec![trait_name])),
+                }
             }
+        }
 
-            TokenType::Nil => {
-                self.advance();
-                Ok(Expr::Literal(Literal {
-                    value: LiteralValue::Nil,
-                }))
-            }
+        let conflicts: Vec<String> = providers
+            .iter()
+            .filter(|(_, traits)| traits.len() > 1)
+            .map(|(name, traits)| format!("'{}' ({})", name, traits.join(", ")))
+            .collect();
+        if !conflicts.is_empty() {
+            let message = format!(
+                "Conflicting trait methods in class '{}': {}.",
+                class.name.lexeme,
+                conflicts.join(", ")
+            );
+            self.errors.push(self.error(&class.name, &message));
+        }
+    }
 
-            TokenType::Number => {
-                let token = self.advance();
-                let num = token.lexeme.parse::<f64>().map_err(|_| {
-                    ParserError::new(
-                        "Invalid number",
-                        token.line as usize,
-                        token.clone(),
-                    )
-                })?;
-                Ok(Expr::Literal(Literal {
-                    value: LiteralValue::Number(num),
-                }))
-            }
+    // method() {}  getter {}  class method() {}  class field = value;
+    fn class_member(&mut self, class: &mut Class) -> Result<(), ParserError> {
+        let doc = self.docs.get(&self.current).cloned();
 
-            TokenType::String => {
-                let token = self.advance();
-                Ok(Expr::Literal(Literal {
-                    value: LiteralValue::String(token.lexeme.clone()),
-                }))
+        if self.match_tokens(&[TokenType::Class]) {
+            let name = self.consume(
+                TokenType::Identifier,
+                "Expect class method or field name.",
+            )?;
+            if self.match_tokens(&[TokenType::Equal]) {
+                let value = self.expression()?;
+                self.end_statement("Expect ';' after class field.")?;
+                class.static_fields.push(name);
+                class.static_values.push(value);
+                return Ok(());
             }
+            self.consume(
+                TokenType::LeftParen,
+                "Expect '(' or '=' after class member name.",
+            )?;
+            let method = self.function_rest(name, "class method", doc)?;
+            class.class_methods.push(method);
+            return Ok(());
+        }
 
-            TokenType::LeftParen => {
-                self.advance();
-                let expr = self.expression()?;
+        let name =
+            self.consume(TokenType::Identifier, "Expect method name.")?;
+        // 괄호 없이 바로 본문이 오면 getter
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            let body = self.function_block()?;
+            class.getters.push(Function {
+                name,
+                params: Vec::new(),
+                defaults: Vec::new(),
+                rest: None,
+                body,
+                doc,
+            });
+            return Ok(());
+        }
+        self.consume(
+            TokenType::LeftParen,
+            "Expect '(' or '{' after method name.",
+        )?;
+        let method = self.function_rest(name, "method", doc)?;
+        class.methods.push(method);
+        Ok(())
+    }
 
-                if self.peek().token_type != TokenType::RightParen {
-                    let token = self.peek().clone();
-                    return Err(ParserError::new(
-                        "Expect ')' after expression",
-                        token.line as usize,
-                        token,
+    fn function(
+        &mut self,
+        kind: &str,
+        doc: Option<String>,
+    ) -> Result<Function, ParserError> {
+        let name = self.consume(
+            TokenType::Identifier,
+            &format!("Expect {} name.", kind),
+        )?;
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+        self.function_rest(name, kind, doc)
+    }
+
+    // 이름과 '(' 다음부터
+    fn function_rest(
+        &mut self,
+        name: Token,
+        kind: &str,
+        doc: Option<String>,
+    ) -> Result<Function, ParserError> {
+        let (params, body) = self.function_body(kind)?;
+
+        Ok(Function {
+            name,
+            params: params.names,
+            defaults: params.defaults,
+            rest: params.rest,
+            body,
+            doc,
+        })
+    }
+
+    // (a, b = 2, ...rest) : 기본값이 있는 매개변수 뒤에는 기본값이 있어야 하고
+    // ...rest는 맨 끝에만 올 수 있다
+    fn parameters(&mut self) -> Result<Parameters, ParserError> {
+        let mut params = Parameters::default();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if params.names.len() >= MAX_ARGUMENTS {
+                    return Err(self.error(
+                        self.peek(),
+                        "Can't have more than 255 parameters.",
                     ));
                 }
-                self.advance();
+                if self.match_tokens(&[TokenType::DotDotDot]) {
+                    params.rest = Some(self.consume(
+                        TokenType::Identifier,
+                        "Expect parameter name after '...'.",
+                    )?);
+                    break;
+                }
 
-                Ok(Expr::Grouping(Grouping {
-                    expression: Box::new(expr),
-                }))
+                let name = self
+                    .consume(TokenType::Identifier, "Expect parameter name.")?;
+                let default = if self.match_tokens(&[TokenType::Equal]) {
+                    Some(self.assignment_expression()?)
+                } else {
+                    if matches!(params.defaults.last(), Some(Some(_))) {
+                        self.errors.push(self.error(
+                            &name,
+                            "Parameter without default can't follow one with a default.",
+                        ));
+                    }
+                    None
+                };
+                params.names.push(name);
+                params.defaults.push(default);
+
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
             }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
 
-            _ => {
-                let token = self.peek().clone();
-                Err(ParserError::new(
-                    "Expect expression",
-                    token.line as usize,
-                    token,
-                ))
+        Ok(params)
+    }
+
+    // '(' 다음의 매개변수 목록과 본문
+    fn function_body(
+        &mut self,
+        kind: &str,
+    ) -> Result<(Parameters, Vec<Stmt>), ParserError> {
+        let params = self.parameters()?;
+
+        self.consume(
+            TokenType::LeftBrace,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+
+        Ok((params, self.function_block()?))
+    }
+
+    // '{' 다음의 함수 본문. 바깥 반복문의 break/continue는 안에서 못 쓴다
+    fn function_block(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let enclosing_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.block();
+        self.loop_depth = enclosing_depth;
+        body
+    }
+
+    fn var_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let stmt = self.var_binding(doc)?;
+        self.end_statement("Expect ';' after variable declaration.")?;
+        Ok(stmt)
+    }
+
+    // var 뒤의 `name (= value)?`. 끝의 ';'는 부르는 쪽에서 처리한다
+    fn var_binding(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let name =
+            self.consume(TokenType::Identifier, "Expect variable name.")?;
+
+        let mut initializer = None;
+        if self.match_tokens(&[TokenType::Equal]) {
+            initializer = Some(self.expression()?);
+        }
+
+        Ok(Stmt::Var(Var {
+            name,
+            initializer,
+            doc,
+        }))
+    }
+
+    // var [a, ...rest] = xs;  var {x, y} = point;
+    fn var_pattern_declaration(
+        &mut self,
+        doc: Option<String>,
+    ) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        let pattern = self.pattern()?;
+        self.check_destructuring(&pattern)?;
+
+        self.consume(
+            TokenType::Equal,
+            "Destructuring declaration requires an initializer.",
+        )?;
+        let initializer = self.expression()?;
+        self.end_statement("Expect ';' after variable declaration.")?;
+
+        Ok(Stmt::VarPattern(VarPattern {
+            keyword,
+            pattern,
+            initializer,
+            doc,
+        }))
+    }
+
+    // if/while/match 본문은 declaration을 거치지 않으므로 여기서 센다
+    fn statement(&mut self) -> Result<Stmt, ParserError> {
+        let checkpoint = self.checkpoint();
+        let first = self.peek().token_type;
+        let stmt = self.nested("Statement", Self::statement_inner)?;
+        self.wrap(checkpoint, NodeKind::of_stmt(&stmt, first));
+        Ok(stmt)
+    }
+
+    fn statement_inner(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_tokens(&[TokenType::Break, TokenType::Continue]) {
+            return self.loop_jump_statement();
+        }
+        if self.match_tokens(&[TokenType::For]) {
+            return self.for_statement();
+        }
+        if self.match_tokens(&[TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.match_tokens(&[TokenType::Match]) {
+            return self.match_statement();
+        }
+        if self.match_tokens(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+        if self.match_tokens(&[TokenType::Return]) {
+            return self.return_statement();
+        }
+        if self.match_tokens(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.match_tokens(&[TokenType::Try]) {
+            return self.try_statement();
+        }
+        if self.match_tokens(&[TokenType::While]) {
+            return self.while_statement();
+        }
+        if matches!(
+            self.peek().token_type,
+            TokenType::LeftBracket | TokenType::LeftBrace
+        ) {
+            if let Some(expression) = self.assign_pattern()? {
+                self.end_statement("Expect ';' after expression.")?;
+                return Ok(Stmt::Expression(Expression { expression }));
             }
         }
+        if !self.starts_map_literal()
+            && self.match_tokens(&[TokenType::LeftBrace])
+        {
+            return Ok(Stmt::Block(Block {
+                statements: self.block_inner()?,
+            }));
+        }
+        self.expression_statement()
     }
 
-    fn is_at_end(&self) -> bool {
-        self.tokens[self.current].token_type == TokenType::Eof
+    // 문장 맨 앞의 '{'는 블록이다. 단 `{ key :` 형태는 블록일 수 없으므로
+    // 맵 리터럴 식 문장으로 본다
+    fn starts_map_literal(&self) -> bool {
+        self.peek().token_type == TokenType::LeftBrace
+            && (self.check_ahead(1, TokenType::String)
+                || self.check_ahead(1, TokenType::Number)
+                || self.check_ahead(1, TokenType::Identifier))
+            && self.check_ahead(2, TokenType::Colon)
     }
 
-    fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+    // for는 while로 풀어서 만든다
+    // 절 파싱과 desugar를 따로 두어 본문 재귀 경로의 스택 프레임을 작게 한다
+    fn for_statement(&mut self) -> Result<Stmt, ParserError> {
+        let clauses = self.for_clauses()?;
+        let body = self.loop_body()?;
+        Ok(clauses.desugar(body))
     }
 
-    fn previous(&self) -> Token {
-        self.tokens[self.current - 1].clone()
+    fn for_clauses(&mut self) -> Result<Box<ForClauses>, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        // 괄호 안이라 ASI 모드에서도 ';'를 끼워 넣지 않는다
+        let initializer = if self.match_tokens(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_tokens(&[TokenType::Var]) {
+            let stmt = self.var_binding(None)?;
+            self.consume(
+                TokenType::Semicolon,
+                "Expect ';' after variable declaration.",
+            )?;
+            Some(stmt)
+        } else {
+            let expression = self.expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+            Some(Stmt::Expression(Expression { expression }))
+        };
+
+        let condition = if self.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        Ok(Box::new(ForClauses {
+            initializer,
+            condition,
+            increment,
+        }))
     }
 
-    fn advance(&mut self) -> Token {
-        if !self.is_at_end() {
-            self.current += 1;
+    fn if_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = if self.match_tokens(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(If {
+            condition,
+            then_branch,
+            else_branch,
+        }))
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, ParserError> {
+        let expression = self.expression()?;
+        self.end_statement("Expect ';' after value.")?;
+        Ok(Stmt::Print(Print { expression }))
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        // ASI 모드에서는 return 바로 뒤의 줄바꿈도 문장 끝이다
+        let value = if self.check(TokenType::Semicolon)
+            || self.can_insert_semicolon()
+        {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        self.end_statement("Expect ';' after return value.")?;
+        Ok(Stmt::Return(Return { keyword, value }))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+
+        self.end_statement("Expect ';' after thrown value.")?;
+        Ok(Stmt::Throw(Throw { keyword, value }))
+    }
+
+    // try { } catch (e) { } finally { } : catch와 finally 중 하나는 있어야 한다
+    fn try_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let mut catch_name = None;
+        let mut catch_body = None;
+        if self.match_tokens(&[TokenType::Catch]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'.")?;
+            catch_name = Some(self.consume(
+                TokenType::Identifier,
+                "Expect exception variable name.",
+            )?);
+            self.consume(
+                TokenType::RightParen,
+                "Expect ')' after exception variable.",
+            )?;
+            self.consume(
+                TokenType::LeftBrace,
+                "Expect '{' after catch clause.",
+            )?;
+            catch_body = Some(self.block()?);
         }
-        self.previous()
+
+        let mut finally_body = None;
+        if self.match_tokens(&[TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            finally_body = Some(self.block()?);
+        }
+
+        if catch_body.is_none() && finally_body.is_none() {
+            let expected = self.expected.clone();
+            return Err(self
+                .error(
+                    self.peek(),
+                    "Expect 'catch' or 'finally' after try block.",
+                )
+                .with_expected(expected));
+        }
+
+        Ok(Stmt::Try(Try {
+            keyword,
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        }))
+    }
+
+    // match (value) { pattern (if guard)? => statement ,? ... }
+    fn match_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'match'.")?;
+        let value = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after match value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before match arms.")?;
+
+        let mut patterns = Vec::new();
+        let mut guards = Vec::new();
+        let mut bodies = Vec::new();
+        let mut catch_all = false;
+
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let arm_start = self.peek().clone();
+            let pattern = self.pattern()?;
+            let guard = if self.match_tokens(&[TokenType::If]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            self.consume(TokenType::FatArrow, "Expect '=>' after pattern.")?;
+            bodies.push(self.statement()?);
+            self.match_tokens(&[TokenType::Comma]);
+
+            if catch_all {
+                self.warnings.push(self.error(
+                    &arm_start,
+                    "Unreachable match arm after wildcard pattern.",
+                ));
+            }
+            if guard.is_none() && Self::matches_anything(&pattern) {
+                catch_all = true;
+            }
+            patterns.push(pattern);
+            guards.push(guard);
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after match arms.")?;
+
+        Ok(Stmt::Match(Match {
+            keyword,
+            value,
+            patterns,
+            guards,
+            bodies,
+        }))
+    }
+
+    // pattern → alternative ( "|" alternative )*
+    fn pattern(&mut self) -> Result<Pattern, ParserError> {
+        let checkpoint = self.checkpoint();
+        let pattern = self.nested("Pattern", Self::pattern_inner)?;
+        self.wrap(checkpoint, NodeKind::Pattern);
+        Ok(pattern)
     }
 
-    fn check(&self, token_type: TokenType) -> bool {
-        if self.is_at_end() {
-            return false;
+    fn pattern_inner(&mut self) -> Result<Pattern, ParserError> {
+        let first = self.alternative()?;
+        if !self.check(TokenType::Pipe) {
+            return Ok(first);
+        }
+
+        let mut alternatives = vec![first];
+        while self.match_tokens(&[TokenType::Pipe]) {
+            alternatives.push(self.alternative()?);
         }
-        self.peek().token_type == token_type
+        Ok(Pattern::Alternation(pattern::Alternation { alternatives }))
     }
 
-    fn match_tokens(&mut self, types: &[TokenType]) -> bool {
-        for (_, t) in types.iter().enumerate() {
-            if self.check(t.clone()) {
+    fn alternative(&mut self) -> Result<Pattern, ParserError> {
+        let token = self.peek().clone();
+
+        match token.token_type {
+            TokenType::Identifier if token.lexeme == "_" => {
                 self.advance();
-                return true;
+                Ok(Pattern::Wildcard(pattern::Wildcard { token }))
+            }
+            TokenType::Identifier
+                if self.check_ahead(1, TokenType::LeftBrace) =>
+            {
+                self.instance_pattern()
+            }
+            TokenType::Identifier => {
+                let name = self.advance();
+                Ok(Pattern::Binding(pattern::Binding { name }))
+            }
+            TokenType::LeftBracket => self.list_pattern(),
+            TokenType::LeftBrace => {
+                let brace = self.advance();
+                let (fields, patterns) = self.field_patterns("object")?;
+                Ok(Pattern::Object(pattern::Object {
+                    brace,
+                    fields,
+                    patterns,
+                }))
+            }
+            TokenType::Minus if self.check_ahead(1, TokenType::Number) => {
+                self.advance();
+                let value = match self.literal_value()? {
+                    LiteralValue::Integer(n) => LiteralValue::Integer(-n),
+                    LiteralValue::Number(n) => LiteralValue::Number(-n),
+                    value => value,
+                };
+                Ok(Pattern::Literal(pattern::Literal { token, value }))
+            }
+            TokenType::Number
+            | TokenType::String
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Nil => {
+                let value = self.literal_value()?;
+                Ok(Pattern::Literal(pattern::Literal { token, value }))
             }
+            _ => Err(self.error(&token, "Expect pattern.")),
         }
-        false
+    }
+
+    // [a, [b, _], ...rest] : ...rest는 맨 끝에만 올 수 있다
+    fn list_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let bracket = self.advance();
+        let mut elements = Vec::new();
+        let mut rest = None;
+
+        while !self.check(TokenType::RightBracket) {
+            if self.match_tokens(&[TokenType::DotDotDot]) {
+                rest = Some(self.consume(
+                    TokenType::Identifier,
+                    "Expect name after '...'.",
+                )?);
+                self.match_tokens(&[TokenType::Comma]);
+                break;
+            }
+            elements.push(self.pattern()?);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RightBracket,
+            "Expect ']' after list pattern.",
+        )?;
+
+        Ok(Pattern::List(pattern::List {
+            bracket,
+            elements,
+            rest,
+        }))
+    }
+
+    // Point { x, y: 0 } : x는 x: x 의 줄임
+    fn instance_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let class_name = self.advance();
+        self.advance();
+        let (fields, patterns) = self.field_patterns("instance")?;
+
+        Ok(Pattern::Instance(pattern::Instance {
+            class_name,
+            fields,
+            patterns,
+        }))
+    }
+
+    // '{' 다음의 `field (: pattern)?` 목록과 '}'
+    fn field_patterns(
+        &mut self,
+        kind: &str,
+    ) -> Result<(Vec<Token>, Vec<Pattern>), ParserError> {
+        let mut fields = Vec::new();
+        let mut patterns = Vec::new();
+        while !self.check(TokenType::RightBrace) {
+            let field =
+                self.consume(TokenType::Identifier, "Expect field name.")?;
+            let pattern = if self.match_tokens(&[TokenType::Colon]) {
+                self.pattern()?
+            } else {
+                Pattern::Binding(pattern::Binding {
+                    name: field.clone(),
+                })
+            };
+            fields.push(field);
+            patterns.push(pattern);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RightBrace,
+            &format!("Expect '}}' after {} pattern.", kind),
+        )?;
+
+        Ok((fields, patterns))
+    }
+
+    // 구조 분해에는 항상 맞는 패턴(이름, _, 리스트, 객체)만 쓸 수 있다
+    fn check_destructuring(
+        &self,
+        pattern: &Pattern,
+    ) -> Result<(), ParserError> {
+        match pattern {
+            Pattern::Binding(_) | Pattern::Wildcard(_) => Ok(()),
+            Pattern::List(list) => list
+                .elements
+                .iter()
+                .try_for_each(|p| self.check_destructuring(p)),
+            Pattern::Object(object) => object
+                .patterns
+                .iter()
+                .try_for_each(|p| self.check_destructuring(p)),
+            Pattern::Alternation(_)
+            | Pattern::Instance(_)
+            | Pattern::Literal(_) => Err(self.error(
+                self.pattern_token(pattern),
+                "Only names, '_', list and object patterns can be destructured.",
+            )),
+        }
+    }
+
+    // `_`, 이름, 또는 그런 대안이 하나라도 있는 `|` 패턴은 모든 값에 맞는다
+    fn matches_anything(pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Binding(_) | Pattern::Wildcard(_) => true,
+            Pattern::Alternation(p) => {
+                p.alternatives.iter().any(Self::matches_anything)
+            }
+            _ => false,
+        }
+    }
+
+    fn pattern_token<'a>(&'a self, pattern: &'a Pattern) -> &'a Token {
+        match pattern {
+            Pattern::Alternation(p) => self.pattern_token(&p.alternatives[0]),
+            Pattern::Binding(p) => &p.name,
+            Pattern::Instance(p) => &p.class_name,
+            Pattern::List(p) => &p.bracket,
+            Pattern::Literal(p) => &p.token,
+            Pattern::Object(p) => &p.brace,
+            Pattern::Wildcard(p) => &p.token,
+        }
+    }
+
+    // 문장 맨 앞의 `[a, b] = ...` / `{x, y} = ...`.
+    // 패턴 뒤에 '='가 없으면 처음 위치로 되돌리고 None
+    fn assign_pattern(&mut self) -> Result<Option<Expr>, ParserError> {
+        let start = self.current;
+        let expected = self.expected.clone();
+        let checkpoint = self.checkpoint();
+
+        if let Ok(pattern) = self.pattern() {
+            if self.match_tokens(&[TokenType::Equal]) {
+                let equals = self.previous();
+                self.check_destructuring(&pattern)?;
+                let value = self.assignment_expression()?;
+                self.wrap(checkpoint, NodeKind::Assign);
+                return Ok(Some(Expr::AssignPattern(AssignPattern {
+                    pattern,
+                    equals,
+                    value: Box::new(value),
+                })));
+            }
+        }
+
+        self.current = start;
+        self.expected = expected;
+        self.rewind(checkpoint);
+        Ok(None)
+    }
+
+    fn literal_value(&mut self) -> Result<LiteralValue, ParserError> {
+        match self.primary()? {
+            Expr::Literal(Literal { value }) => Ok(value),
+            _ => Err(self.error(&self.previous(), "Expect literal.")),
+        }
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = Box::new(self.loop_body()?);
+
+        Ok(Stmt::While(While {
+            condition,
+            body,
+            increment: None,
+        }))
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, ParserError> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
+    }
+
+    fn loop_jump_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        if self.loop_depth == 0 {
+            let message =
+                format!("Can't use '{}' outside of a loop.", keyword.lexeme);
+            self.errors.push(self.error(&keyword, &message));
+        }
+        self.end_statement(&format!("Expect ';' after '{}'.", keyword.lexeme))?;
+
+        if keyword.token_type == TokenType::Break {
+            Ok(Stmt::Break(Break { keyword }))
+        } else {
+            Ok(Stmt::Continue(Continue { keyword }))
+        }
+    }
+
+    // 함수 본문은 statement를 거치지 않으므로 여기서도 센다
+    fn block(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        self.nested("Statement", Self::block_inner)
+    }
+
+    fn block_inner(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut statements = Vec::new();
+        self.block_depth += 1;
+
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let checkpoint = self.checkpoint();
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                // 파일 끝에서 난 에러는 바깥 블록마다 되풀이되지 않게 올려 보낸다
+                Err(error) if self.is_at_end() => {
+                    self.block_depth -= 1;
+                    return Err(error);
+                }
+                // 깨진 문장만 빼고 블록의 나머지 문장은 계속 파싱한다
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                    self.wrap(checkpoint, NodeKind::Error);
+                }
+            }
+        }
+        self.block_depth -= 1;
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ParserError> {
+        let expression = self.expression()?;
+        self.end_statement("Expect ';' after expression.")?;
+        Ok(Stmt::Expression(Expression { expression }))
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParserError> {
+        self.parse_precedence(Precedence::Comma)
+    }
+
+    // 콤마로 구분되는 목록 안에서는 콤마 연산자를 쓰지 않는다
+    fn assignment_expression(&mut self) -> Result<Expr, ParserError> {
+        self.parse_precedence(Precedence::Assignment)
+    }
+
+    // Pratt parser 본체: min 이상으로 묶이는 infix 연산자만 이어 붙인다
+    fn parse_precedence(
+        &mut self,
+        min: Precedence,
+    ) -> Result<Expr, ParserError> {
+        self.nested("Expression", |parser| parser.precedence_loop(min))
+    }
+
+    fn precedence_loop(
+        &mut self,
+        min: Precedence,
+    ) -> Result<Expr, ParserError> {
+        let prefix = match rule(self.peek().token_type).prefix {
+            Some(prefix) => prefix,
+            None => return Err(self.error(self.peek(), "Expect expression")),
+        };
+        let checkpoint = self.checkpoint();
+        // 같은 목록의 앞 식들 (f(a, b)의 a 등). 이 식과 합쳐서 돌려준다
+        let enclosing = std::mem::take(&mut self.height);
+        let mut expr = prefix(self)?;
+        self.wrap(checkpoint, NodeKind::of_expr(&expr));
+        let mut height = self.height + 1;
+        // ?. 를 만난 뒤로 . ?. () [] 가 이어지는 동안은 같은 chain이다
+        let mut in_chain = false;
+
+        loop {
+            let rule = rule(self.peek().token_type);
+            let infix = match rule.infix {
+                Some(infix) if rule.precedence >= min => infix,
+                _ => break,
+            };
+            if self.starts_new_statement() {
+                break;
+            }
+            if height >= MAX_EXPRESSION_HEIGHT {
+                return Err(self.error(self.peek(), "Expression too long"));
+            }
+            let operator = self.advance();
+            in_chain |= operator.token_type == TokenType::QuestionDot;
+            self.height = 0;
+            expr = infix(self, expr)?;
+            self.wrap(checkpoint, NodeKind::of_expr(&expr));
+            // 오른쪽 피연산자, 인자 등이 왼쪽보다 높을 수도 있다
+            height = height.max(self.height) + 1;
+
+            if in_chain && !self.continues_chain() {
+                expr = Expr::OptionalChain(OptionalChain {
+                    expression: Box::new(expr),
+                });
+                self.wrap(checkpoint, NodeKind::OptionalChain);
+                height += 1;
+                in_chain = false;
+            }
+        }
+
+        self.height = enclosing.max(height);
+        Ok(expr)
+    }
+
+    // 문장 끝의 ';'. ASI 모드에서는 다음 토큰이 새 줄에 있거나 '}' / 파일 끝이면
+    // 없어도 넣은 것으로 친다
+    fn end_statement(&mut self, message: &str) -> Result<(), ParserError> {
+        if self.match_tokens(&[TokenType::Semicolon]) {
+            return Ok(());
+        }
+        if self.can_insert_semicolon() {
+            self.inserted_semicolons.push(self.previous());
+            return Ok(());
+        }
+        self.consume(TokenType::Semicolon, message).map(|_| ())
+    }
+
+    fn can_insert_semicolon(&self) -> bool {
+        self.automatic_semicolons
+            && (matches!(
+                self.peek().token_type,
+                TokenType::RightBrace | TokenType::Eof
+            ) || self.peek().line > self.previous().line)
+    }
+
+    // ASI 모드에서 줄이 바뀐 뒤의 ++ / -- 는 후위가 아니라 다음 문장의 전위다
+    fn starts_new_statement(&self) -> bool {
+        self.automatic_semicolons
+            && matches!(
+                self.peek().token_type,
+                TokenType::PlusPlus | TokenType::MinusMinus
+            )
+            && self.peek().line > self.previous().line
+    }
+
+    // 재귀 한 단계. 한도를 넘으면 더 내려가지 않고 에러를 돌려준다
+    fn nested<T>(
+        &mut self,
+        what: &str,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        if self.depth >= self.max_depth {
+            let message = format!("{} nested too deeply", what);
+            return Err(self.error(self.peek(), &message));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn continues_chain(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::Dot
+                | TokenType::QuestionDot
+                | TokenType::LeftParen
+                | TokenType::LeftBracket
+        )
+    }
+
+    // 오른쪽 피연산자를 파싱할 최소 우선순위
+    fn right_operand(&mut self, operator: &Token) -> Result<Expr, ParserError> {
+        let rule = rule(operator.token_type);
+        match rule.associativity {
+            Associativity::Left => {
+                self.parse_precedence(rule.precedence.next())
+            }
+            Associativity::Right => self.parse_precedence(rule.precedence),
+        }
+    }
+
+    fn assignment(&mut self, target: Expr) -> Result<Expr, ParserError> {
+        let equals = self.previous();
+        let value = Box::new(self.right_operand(&equals)?);
+
+        match target {
+            Expr::Variable(Variable { name }) => {
+                Ok(Expr::Assign(Assign { name, value }))
+            }
+            Expr::Get(Get { object, name }) => Ok(Expr::Set(Set {
+                object,
+                name,
+                value,
+            })),
+            Expr::Index(Index {
+                object,
+                bracket,
+                index,
+            }) => Ok(Expr::SetIndex(SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+            })),
+            _ => Err(self.error(&equals, "Invalid assignment target.")),
+        }
+    }
+
+    fn is_assignable(target: &Expr) -> bool {
+        matches!(target, Expr::Variable(_) | Expr::Get(_) | Expr::Index(_))
+    }
+
+    // a += b 등. 대상 식을 그대로 들고 있어야 한 번만 평가할 수 있다
+    fn compound_assignment(
+        &mut self,
+        target: Expr,
+    ) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        let value = self.right_operand(&operator)?;
+
+        if !Self::is_assignable(&target) {
+            return Err(self.error(&operator, "Invalid assignment target."));
+        }
+        Ok(Expr::CompoundAssign(CompoundAssign {
+            target: Box::new(target),
+            operator,
+            value: Box::new(value),
+        }))
+    }
+
+    // ++x, --x
+    fn prefix_update(&mut self) -> Result<Expr, ParserError> {
+        let operator = self.advance();
+        let target = self.parse_precedence(Precedence::Call)?;
+        self.update(target, operator, true)
+    }
+
+    // x++, x--
+    fn postfix_update(&mut self, target: Expr) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        self.update(target, operator, false)
+    }
+
+    fn update(
+        &mut self,
+        target: Expr,
+        operator: Token,
+        prefix: bool,
+    ) -> Result<Expr, ParserError> {
+        if !Self::is_assignable(&target) {
+            let message = format!("Invalid '{}' operand.", operator.lexeme);
+            return Err(self.error(&operator, &message));
+        }
+        Ok(Expr::Update(Update {
+            target: Box::new(target),
+            operator,
+            prefix,
+        }))
+    }
+
+    fn comma(&mut self, left: Expr) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        let right = self.right_operand(&operator)?;
+
+        Ok(Expr::Comma(Comma {
+            left: Box::new(left),
+            right: Box::new(right),
+        }))
+    }
+
+    // 가운데 식은 괄호처럼 취급되어 어떤 식이든 올 수 있다
+    fn conditional(&mut self, condition: Expr) -> Result<Expr, ParserError> {
+        let question = self.previous();
+        let then_branch = self.expression()?;
+        self.consume(
+            TokenType::Colon,
+            "Expect ':' after then branch of conditional expression.",
+        )?;
+        let else_branch = self.right_operand(&question)?;
+
+        Ok(Expr::Conditional(Conditional {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        }))
+    }
+
+    fn logical(&mut self, left: Expr) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        let right = self.right_operand(&operator)?;
+
+        Ok(Expr::Logical(Logical {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }))
+    }
+
+    fn coalesce(&mut self, left: Expr) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        let right = self.right_operand(&operator)?;
+
+        Ok(Expr::Coalesce(Coalesce {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }))
+    }
+
+    fn binary(&mut self, left: Expr) -> Result<Expr, ParserError> {
+        let operator = self.previous();
+        let right = self.right_operand(&operator)?;
+
+        Ok(Expr::Binary(Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }))
+    }
+
+    // error production: `== x`처럼 왼쪽 피연산자가 없는 이항 연산자.
+    // 에러만 기록하고 오른쪽 피연산자는 파싱해서 버린다
+    fn missing_left_operand(&mut self) -> Result<Expr, ParserError> {
+        let operator = self.advance();
+        let message = format!(
+            "Binary operator '{}' missing left-hand operand",
+            operator.lexeme
+        );
+        self.errors.push(self.error(&operator, &message));
+        self.right_operand(&operator)?;
+
+        Ok(Expr::Literal(Literal {
+            value: LiteralValue::Nil,
+        }))
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParserError> {
+        let operator = self.advance();
+        let right = self.parse_precedence(Precedence::Unary)?;
+
+        Ok(Expr::Unary(Unary {
+            operator,
+            right: Box::new(right),
+        }))
+    }
+
+    fn lambda(&mut self) -> Result<Expr, ParserError> {
+        let keyword = self.advance();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+        let (params, body) = self.function_body("anonymous function")?;
+
+        Ok(Expr::Lambda(Lambda {
+            keyword,
+            params: params.names,
+            defaults: params.defaults,
+            rest: params.rest,
+            body,
+        }))
+    }
+
+    // [a, b, c] (마지막 콤마 허용)
+    fn list(&mut self) -> Result<Expr, ParserError> {
+        let bracket = self.advance();
+        let mut elements = Vec::new();
+
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.assignment_expression()?);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RightBracket,
+            "Expect ']' after list elements.",
+        )?;
+
+        Ok(Expr::List(List { bracket, elements }))
+    }
+
+    // {key: value, ...} (마지막 콤마 허용, 순서 유지)
+    fn map(&mut self) -> Result<Expr, ParserError> {
+        let brace = self.advance();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        while !self.check(TokenType::RightBrace) {
+            keys.push(self.assignment_expression()?);
+            self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+            values.push(self.assignment_expression()?);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+
+        Ok(Expr::Map(Map {
+            brace,
+            keys,
+            values,
+        }))
+    }
+
+    fn index(&mut self, object: Expr) -> Result<Expr, ParserError> {
+        let bracket = self.previous();
+        let index = self.expression()?;
+        self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+
+        Ok(Expr::Index(Index {
+            object: Box::new(object),
+            bracket,
+            index: Box::new(index),
+        }))
+    }
+
+    fn get(&mut self, object: Expr) -> Result<Expr, ParserError> {
+        let name = self.consume(
+            TokenType::Identifier,
+            "Expect property name after '.'.",
+        )?;
+
+        Ok(Expr::Get(Get {
+            object: Box::new(object),
+            name,
+        }))
+    }
+
+    // a?.b : a가 nil이면 b를 찾지 않고, 감싸는 OptionalChain 전체가 nil이 된다
+    fn optional_get(&mut self, object: Expr) -> Result<Expr, ParserError> {
+        let name = self.consume(
+            TokenType::Identifier,
+            "Expect property name after '?.'.",
+        )?;
+
+        Ok(Expr::OptionalGet(OptionalGet {
+            object: Box::new(object),
+            name,
+        }))
+    }
+
+    // f(1, b: 3) : 이름 붙인 인자 뒤에는 위치 인자가 올 수 없다
+    fn call(&mut self, callee: Expr) -> Result<Expr, ParserError> {
+        let mut arguments = Vec::new();
+        let mut names: Vec<Option<Token>> = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    return Err(self.error(
+                        self.peek(),
+                        "Can't have more than 255 arguments.",
+                    ));
+                }
+
+                let name = if self.check(TokenType::Identifier)
+                    && self.check_ahead(1, TokenType::Colon)
+                {
+                    let name = self.advance();
+                    self.advance();
+                    if names.iter().flatten().any(|n| n.lexeme == name.lexeme) {
+                        self.errors.push(
+                            self.error(&name, "Duplicate named argument."),
+                        );
+                    }
+                    Some(name)
+                } else {
+                    if matches!(names.last(), Some(Some(_))) {
+                        self.errors.push(self.error(
+                            self.peek(),
+                            "Positional argument can't follow a named argument.",
+                        ));
+                    }
+                    None
+                };
+                names.push(name);
+                arguments.push(self.assignment_expression()?);
+
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        let paren =
+            self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+
+        Ok(Expr::Call(Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+            names,
+        }))
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParserError> {
+        let token = self.peek();
+
+        match token.token_type {
+            TokenType::False => {
+                self.advance();
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::Boolean(false),
+                }))
+            }
+
+            TokenType::True => {
+                self.advance();
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::Boolean(true),
+                }))
+            }
+
+            TokenType::Nil => {
+                self.advance();
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::Nil,
+                }))
+            }
+
+            // 소수점이 없으면 정수(i64)
+            TokenType::Number if !self.peek().lexeme.contains('.') => {
+                let token = self.advance();
+                let num = token.lexeme.parse::<i64>().map_err(|_| {
+                    ParserError::new(
+                        "Integer literal is too large.",
+                        token.line as usize,
+                        token.clone(),
+                    )
+                })?;
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::Integer(num),
+                }))
+            }
+
+            TokenType::Number => {
+                let token = self.advance();
+                let num = token.lexeme.parse::<f64>().map_err(|_| {
+                    ParserError::new(
+                        "Invalid number",
+                        token.line as usize,
+                        token.clone(),
+                    )
+                })?;
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::Number(num),
+                }))
+            }
+
+            TokenType::String => {
+                let token = self.advance();
+                Ok(Expr::Literal(Literal {
+                    value: LiteralValue::String(
+                        token.literal.clone().unwrap_or_default(),
+                    ),
+                }))
+            }
+
+            TokenType::This => {
+                let keyword = self.advance();
+                Ok(Expr::This(This { keyword }))
+            }
+
+            TokenType::Super => {
+                let keyword = self.advance();
+                self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+                let method = self.consume(
+                    TokenType::Identifier,
+                    "Expect superclass method name.",
+                )?;
+                Ok(Expr::Super(Super { keyword, method }))
+            }
+
+            TokenType::Identifier => {
+                let name = self.advance();
+                Ok(Expr::Variable(Variable { name }))
+            }
+
+            TokenType::LeftParen => {
+                self.advance();
+                let expr = self.expression()?;
+
+                self.consume(
+                    TokenType::RightParen,
+                    "Expect ')' after expression",
+                )?;
+
+                Ok(Expr::Grouping(Grouping {
+                    expression: Box::new(expr),
+                }))
+            }
+
+            _ => {
+                let token = self.peek().clone();
+                Err(ParserError::new(
+                    "Expect expression",
+                    token.line as usize,
+                    token,
+                ))
+            }
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.tokens[self.current].token_type == TokenType::Eof
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> Token {
+        self.tokens[self.current - 1].clone()
+    }
+
+    fn advance(&mut self) -> Token {
+        if !self.is_at_end() {
+            if let Some(tree) = &mut self.tree {
+                tree.token(&self.tokens[self.current]);
+            }
+            self.current += 1;
+            self.expected.clear();
+        }
+        self.previous()
+    }
+
+    // distance만큼 앞의 토큰을 미리 본다 (expected에는 기록하지 않음)
+    fn check_ahead(&self, distance: usize, token_type: TokenType) -> bool {
+        match self.tokens.get(self.current + distance) {
+            Some(token) => token.token_type == token_type,
+            None => false,
+        }
+    }
+
+    fn check(&mut self, token_type: TokenType) -> bool {
+        if !self.is_at_end() && self.peek().token_type == token_type {
+            return true;
+        }
+        if !self.expected.contains(&token_type) {
+            self.expected.push(token_type);
+        }
+        false
+    }
+
+    fn consume(
+        &mut self,
+        token_type: TokenType,
+        message: &str,
+    ) -> Result<Token, ParserError> {
+        if self.check(token_type) {
+            return Ok(self.advance());
+        }
+        let expected = self.expected.clone();
+        Err(self.error(self.peek(), message).with_expected(expected))
+    }
+
+    fn error(&self, token: &Token, message: &str) -> ParserError {
+        ParserError::new(message, token.line as usize, token.clone())
+    }
+
+    // 에러 후 다음 문장 시작까지 건너뛴다. 블록 안에서는 블록을 닫는 '}'
+    // 앞에서 멈춘다
+    fn synchronize(&mut self) {
+        if self.block_depth > 0
+            && self.peek().token_type == TokenType::RightBrace
+        {
+            return;
+        }
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type == TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type {
+                TokenType::Class
+                | TokenType::Trait
+                | TokenType::Import
+                | TokenType::Export
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::For
+                | TokenType::If
+                | TokenType::Match
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return
+                | TokenType::Throw
+                | TokenType::Try
+                | TokenType::Break
+                | TokenType::Continue => return,
+                TokenType::RightBrace if self.block_depth > 0 => return,
+                _ => {}
+            }
+
+            self.advance();
+        }
+    }
+
+    // CST 노드는 파싱이 끝난 뒤 checkpoint부터 감싼다 (트리가 없으면 무시)
+    fn checkpoint(&mut self) -> Checkpoint {
+        let start = self.peek().span.start;
+        match &mut self.tree {
+            Some(tree) => tree.checkpoint(start),
+            None => Checkpoint::default(),
+        }
+    }
+
+    fn wrap(&mut self, checkpoint: Checkpoint, kind: NodeKind) {
+        if let Some(tree) = &mut self.tree {
+            tree.wrap(checkpoint, kind);
+        }
+    }
+
+    // self.current를 checkpoint 위치로 되돌린 뒤에 부른다
+    fn rewind(&mut self, checkpoint: Checkpoint) {
+        let start = self.peek().span.start;
+        if let Some(tree) = &mut self.tree {
+            tree.rewind(checkpoint, start);
+        }
+    }
+
+    fn match_tokens(&mut self, types: &[TokenType]) -> bool {
+        for t in types {
+            if self.check(*t) {
+                self.advance();
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+    use crate::signature::Signature;
+    use crate::{cst, token::Token};
+
+    type Parsed = (Result<Vec<Stmt>, Vec<ParserError>>, Vec<ParserError>);
+
+    // 파싱 결과와 경고. 같은 설정으로 CST도 만들어 원문이 그대로 남는지,
+    // 문장과 에러가 Parser::parse와 같은지 확인한다
+    fn parse_with(source: &str, configure: fn(Parser) -> Parser) -> Parsed {
+        let tokens = scan(source);
+        let mut parser = configure(Parser::new(tokens.clone()));
+        let result = parser.parse();
+        let warnings = parser.take_warnings();
+
+        let cst = cst::parse(source, configure(Parser::new(tokens)));
+        assert_eq!(cst.root.text(), source);
+        match &result {
+            Ok(statements) => {
+                assert!(cst.errors.is_empty(), "{:?}", cst.errors);
+                assert_eq!(&cst.statements, statements);
+            }
+            Err(errors) => {
+                assert_eq!(format!("{:?}", cst.errors), format!("{:?}", errors))
+            }
+        }
+        assert_eq!(format!("{:?}", cst.warnings), format!("{:?}", warnings));
+
+        (result, warnings)
+    }
+
+    fn scan(source: &str) -> Vec<Token> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.scan_tokens().clone()
+    }
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        match parse_with(source, |parser| parser).0 {
+            Ok(statements) => statements,
+            Err(errors) => panic!("parse failed: {:?}", errors),
+        }
+    }
+
+    fn parse_errors(source: &str) -> Vec<ParserError> {
+        match parse_with(source, |parser| parser).0 {
+            Ok(statements) => panic!("expected errors, got {:?}", statements),
+            Err(errors) => errors,
+        }
+    }
+
+    // 식만 파싱한다. CST는 식 문장으로 만들어 원문이 남는지만 본다
+    fn parse_expr(source: &str) -> Result<Expr, ParserError> {
+        let parser = Parser::new(scan(source)).with_automatic_semicolons(true);
+        assert_eq!(cst::parse(source, parser).root.text(), source);
+
+        Parser::new(scan(source)).parse_expression()
+    }
+
+    // 괄호로 묶은 S-expression 형태로 출력 (구조 비교용)
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Assign(e) => {
+                format!("(= {} {})", e.name.lexeme, show(&e.value))
+            }
+            Expr::AssignPattern(e) => {
+                format!("(= {} {})", show_pattern(&e.pattern), show(&e.value))
+            }
+            Expr::Binary(e) => format!(
+                "({} {} {})",
+                e.operator.lexeme,
+                show(&e.left),
+                show(&e.right)
+            ),
+            Expr::Call(e) => {
+                let args: Vec<String> = e
+                    .arguments
+                    .iter()
+                    .zip(&e.names)
+                    .map(|(arg, name)| match name {
+                        Some(name) => format!("{}: {}", name.lexeme, show(arg)),
+                        None => show(arg),
+                    })
+                    .collect();
+                format!("(call {} {})", show(&e.callee), args.join(" "))
+            }
+            Expr::Coalesce(e) => {
+                format!("(?? {} {})", show(&e.left), show(&e.right))
+            }
+            Expr::Comma(e) => {
+                format!("(, {} {})", show(&e.left), show(&e.right))
+            }
+            Expr::CompoundAssign(e) => format!(
+                "({} {} {})",
+                e.operator.lexeme,
+                show(&e.target),
+                show(&e.value)
+            ),
+            Expr::Conditional(e) => format!(
+                "(?: {} {} {})",
+                show(&e.condition),
+                show(&e.then_branch),
+                show(&e.else_branch)
+            ),
+            Expr::Get(e) => {
+                format!("(. {} {})", show(&e.object), e.name.lexeme)
+            }
+            Expr::Grouping(e) => format!("(group {})", show(&e.expression)),
+            Expr::Index(e) => {
+                format!("([] {} {})", show(&e.object), show(&e.index))
+            }
+            Expr::Lambda(e) => {
+                let params: Vec<&str> =
+                    e.params.iter().map(|p| p.lexeme.as_str()).collect();
+                format!("(fun ({}) {} stmts)", params.join(" "), e.body.len())
+            }
+            Expr::List(e) => {
+                let elements: Vec<String> =
+                    e.elements.iter().map(show).collect();
+                format!("[{}]", elements.join(" "))
+            }
+            Expr::Literal(e) => match &e.value {
+                LiteralValue::Number(n) => format!("{:?}", n),
+                LiteralValue::Integer(n) => n.to_string(),
+                LiteralValue::String(s) => s.clone(),
+                LiteralValue::Boolean(b) => b.to_string(),
+                LiteralValue::Nil => "nil".to_string(),
+            },
+            Expr::Logical(e) => format!(
+                "({} {} {})",
+                e.operator.lexeme,
+                show(&e.left),
+                show(&e.right)
+            ),
+            Expr::Map(e) => {
+                let entries: Vec<String> = e
+                    .keys
+                    .iter()
+                    .zip(&e.values)
+                    .map(|(k, v)| format!("{}: {}", show(k), show(v)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Expr::OptionalChain(e) => {
+                format!("(chain {})", show(&e.expression))
+            }
+            Expr::OptionalGet(e) => {
+                format!("(?. {} {})", show(&e.object), e.name.lexeme)
+            }
+            Expr::Set(e) => format!(
+                "(= (. {} {}) {})",
+                show(&e.object),
+                e.name.lexeme,
+                show(&e.value)
+            ),
+            Expr::SetIndex(e) => format!(
+                "(= ([] {} {}) {})",
+                show(&e.object),
+                show(&e.index),
+                show(&e.value)
+            ),
+            Expr::Super(e) => format!("(super {})", e.method.lexeme),
+            Expr::This(_) => "this".to_string(),
+            Expr::Unary(e) => {
+                format!("({} {})", e.operator.lexeme, show(&e.right))
+            }
+            Expr::Update(e) if e.prefix => {
+                format!("({}pre {})", e.operator.lexeme, show(&e.target))
+            }
+            Expr::Update(e) => {
+                format!("({}post {})", e.operator.lexeme, show(&e.target))
+            }
+            Expr::Variable(e) => e.name.lexeme.clone(),
+        }
+    }
+
+    fn show_pattern(pattern: &Pattern) -> String {
+        match pattern {
+            Pattern::Alternation(p) => {
+                let alternatives: Vec<String> =
+                    p.alternatives.iter().map(show_pattern).collect();
+                alternatives.join(" | ")
+            }
+            Pattern::Binding(p) => p.name.lexeme.clone(),
+            Pattern::Instance(p) => format!(
+                "{} {}",
+                p.class_name.lexeme,
+                show_fields(&p.fields, &p.patterns)
+            ),
+            Pattern::List(p) => {
+                let mut elements: Vec<String> =
+                    p.elements.iter().map(show_pattern).collect();
+                if let Some(rest) = &p.rest {
+                    elements.push(format!("...{}", rest.lexeme));
+                }
+                format!("[{}]", elements.join(" "))
+            }
+            Pattern::Literal(p) => p.token.lexeme.clone(),
+            Pattern::Object(p) => show_fields(&p.fields, &p.patterns),
+            Pattern::Wildcard(_) => "_".to_string(),
+        }
+    }
+
+    fn show_fields(fields: &[Token], patterns: &[Pattern]) -> String {
+        let fields: Vec<String> = fields
+            .iter()
+            .zip(patterns)
+            .map(|(f, p)| format!("{}: {}", f.lexeme, show_pattern(p)))
+            .collect();
+        format!("{{{}}}", fields.join(", "))
+    }
+
+    fn assert_parses(source: &str, expected: &str) {
+        match parse_expr(source) {
+            Ok(expr) => assert_eq!(show(&expr), expected, "source: {}", source),
+            Err(error) => panic!("parse failed: {}", error.message),
+        }
+    }
+
+    #[test]
+    fn test_doc_comment_on_function() {
+        let statements =
+            parse("/// Adds two numbers.\n/// Returns the sum.\nfun add(a, b) { return a + b; }");
+
+        match &statements[0] {
+            Stmt::Function(function) => {
+                assert_eq!(function.name.lexeme, "add");
+                assert_eq!(
+                    function.doc.as_deref(),
+                    Some("Adds two numbers.\nReturns the sum.")
+                );
+            }
+            stmt => panic!("expected function, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_doc_comment_on_class_and_method() {
+        let statements =
+            parse("/// A point.\nclass Point {\n  /// Length.\n  len() {}\n  area() {}\n}");
+
+        match &statements[0] {
+            Stmt::Class(class) => {
+                assert_eq!(class.doc.as_deref(), Some("A point."));
+                assert_eq!(class.methods[0].doc.as_deref(), Some("Length."));
+                assert_eq!(class.methods[1].doc, None);
+            }
+            stmt => panic!("expected class, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_doc_comment_on_var() {
+        let statements = parse("/// The answer.\nvar x = 42;\nvar y;");
+
+        match (&statements[0], &statements[1]) {
+            (Stmt::Var(x), Stmt::Var(y)) => {
+                assert_eq!(x.doc.as_deref(), Some("The answer."));
+                assert_eq!(y.doc, None);
+            }
+            stmts => panic!("expected vars, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_stray_doc_comment_ignored() {
+        let statements = parse("print 1 +\n/// not attached\n2;");
+
+        assert_eq!(statements.len(), 1);
+        assert!(matches!(statements[0], Stmt::Print(_)));
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_parses("1 + 2 * 3", "(+ 1 (* 2 3))");
+        assert_parses("1 * 2 + 3", "(+ (* 1 2) 3)");
+        assert_parses("1 < 2 == 3 > 4", "(== (< 1 2) (> 3 4))");
+        assert_parses("a or b and c", "(or a (and b c))");
+        assert_parses("-a.b(1)", "(- (call (. a b) 1))");
+        assert_parses("!!x", "(! (! x))");
+    }
+
+    #[test]
+    fn test_associativity() {
+        assert_parses("1 - 2 - 3", "(- (- 1 2) 3)");
+        assert_parses("8 / 4 / 2", "(/ (/ 8 4) 2)");
+        assert_parses("a = b = c", "(= a (= b c))");
+        assert_parses("a.b = c or d", "(= (. a b) (or c d))");
+    }
+
+    #[test]
+    fn test_grouping_and_calls() {
+        assert_parses("(1 + 2) * 3", "(* (group (+ 1 2)) 3)");
+        assert_parses("f(1, 2)(3)", "(call (call f 1 2) 3)");
+    }
+
+    #[test]
+    fn test_string_literal_value() {
+        match parse_expr("\"hi\"").unwrap() {
+            Expr::Literal(Literal {
+                value: LiteralValue::String(s),
+            }) => assert_eq!(s, "hi"),
+            expr => panic!("expected string literal, got {:?}", expr),
+        }
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let error = parse_expr("a + b = c").err().unwrap();
+
+        assert_eq!(error.message, "Invalid assignment target.");
+        assert_eq!(error.token.lexeme, "=");
+    }
+
+    #[test]
+    fn test_missing_expression() {
+        let error = parse_expr("1 + ;").err().unwrap();
+
+        assert_eq!(error.message, "Expect expression");
+        assert_eq!(error.token.token_type, TokenType::Semicolon);
+    }
+
+    #[test]
+    fn test_recovers_inside_blocks() {
+        let errors = parse_errors(
+            "fun f() {\n  print ;\n  var = 1;\n  print 2;\n}\nprint 3",
+        );
+        let lines: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            lines,
+            [
+                (2, "Expect expression"),
+                (3, "Expect variable name."),
+                (6, "Expect ';' after value.")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_at_end_inside_blocks_reported_once() {
+        let errors = parse_errors("{ { { print 1");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Expect ';' after value.");
+
+        let errors = parse_errors("{ { { print 1;");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Expect '}' after block.");
+    }
+
+    #[test]
+    fn test_conditional() {
+        assert_parses("a ? b : c", "(?: a b c)");
+        assert_parses("a ? b : c ? d : e", "(?: a b (?: c d e))");
+        assert_parses("a ? b ? c : d : e", "(?: a (?: b c d) e)");
+        assert_parses(
+            "a == b ? c + 1 : d or e",
+            "(?: (== a b) (+ c 1) (or d e))",
+        );
+        assert_parses("x = a ? b : c", "(= x (?: a b c))");
+        assert_parses("a ? b, c : d", "(?: a (, b c) d)");
+    }
+
+    #[test]
+    fn test_conditional_missing_colon() {
+        let error = parse_expr("a ? b;").err().unwrap();
+
+        assert_eq!(
+            error.message,
+            "Expect ':' after then branch of conditional expression."
+        );
+    }
+
+    #[test]
+    fn test_comma() {
+        assert_parses("a, b, c", "(, (, a b) c)");
+        assert_parses("a = 1, b = 2", "(, (= a 1) (= b 2))");
+        assert_parses("f(a, b)", "(call f a b)");
+        assert_parses("f((a, b))", "(call f (group (, a b)))");
+    }
+
+    #[test]
+    fn test_missing_left_operand() {
+        let error = parse_expr("+ 3").err().unwrap();
+
+        assert_eq!(
+            error.message,
+            "Binary operator '+' missing left-hand operand"
+        );
+        assert_eq!(error.token.lexeme, "+");
+    }
+
+    #[test]
+    fn test_missing_left_operand_continues() {
+        let errors = parse_errors("== x;\nprint 1 + * 2;\nvar a = 1;\nvar;");
+
+        let messages: Vec<&str> =
+            errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Binary operator '==' missing left-hand operand",
+                "Binary operator '*' missing left-hand operand",
+                "Expect variable name.",
+            ]
+        );
+        assert_eq!(errors[1].line, 2);
+    }
+
+    #[test]
+    fn test_expected_single_token() {
+        let error = parse_expr("(1 + 2").err().unwrap();
+
+        assert_eq!(error.expected, [TokenType::RightParen]);
+        assert_eq!(
+            error.to_string(),
+            "Expect ')' after expression (expected `)`, found end of file)"
+        );
+    }
+
+    #[test]
+    fn test_expected_two_tokens() {
+        let errors = parse_errors("f(a;");
+
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `,` or `)`, found `;`"
+        );
+    }
+
+    #[test]
+    fn test_expected_one_of() {
+        let errors = parse_errors("class A < B C");
+
+        assert_eq!(errors[0].token.lexeme, "C");
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `with` or `{`, found `C`"
+        );
+
+        let errors = parse_errors("var x 1;");
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `=` or `;`, found `1`"
+        );
+
+        let errors = parse_errors("fun f(a b) {}");
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected one of `=`, `,` or `)`, found `b`"
+        );
+    }
+
+    #[test]
+    fn test_expected_not_reported_for_expression() {
+        let error = parse_expr("1 + ;").err().unwrap();
+
+        assert!(error.expected.is_empty());
+        assert_eq!(error.to_string(), "Expect expression");
+    }
+
+    #[test]
+    fn test_lambda_expression() {
+        assert_parses("fun (a, b) { return a + b; }", "(fun (a b) 1 stmts)");
+        assert_parses("apply(fun () {}, 1)", "(call apply (fun () 0 stmts) 1)");
+        assert_parses("f = fun (x) { print x; }", "(= f (fun (x) 1 stmts))");
+    }
+
+    #[test]
+    fn test_lambda_at_statement_start() {
+        let statements = parse("fun named() {}\nfun () {}();");
+
+        assert!(matches!(statements[0], Stmt::Function(_)));
+        match &statements[1] {
+            Stmt::Expression(stmt) => {
+                assert!(matches!(stmt.expression, Expr::Call(_)))
+            }
+            stmt => panic!("expected expression statement, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_lambda_missing_paren() {
+        let errors = parse_errors("var f = fun {};");
+
+        assert_eq!(errors[0].message, "Expect '(' after 'fun'.");
+    }
+
+    #[test]
+    fn test_list_literal() {
+        assert_parses("[]", "[]");
+        assert_parses("[1, 2 + 3, [4]]", "[1 (+ 2 3) [4]]");
+        assert_parses("[1, 2,]", "[1 2]");
+    }
+
+    #[test]
+    fn test_index() {
+        assert_parses("xs[0]", "([] xs 0)");
+        assert_parses("xs[-1]", "([] xs (- 1))");
+        assert_parses("grid[i][j].name", "(. ([] ([] grid i) j) name)");
+        assert_parses("f()[0]", "([] (call f ) 0)");
+    }
+
+    #[test]
+    fn test_index_assignment() {
+        assert_parses("xs[i] = v", "(= ([] xs i) v)");
+        assert_parses("xs[0] = ys[1] = 2", "(= ([] xs 0) (= ([] ys 1) 2))");
+        assert_parses("a.b[c] = d", "(= ([] (. a b) c) d)");
+    }
+
+    #[test]
+    fn test_unclosed_list() {
+        let errors = parse_errors("var xs = [1, 2;");
+
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `,` or `]`, found `;`"
+        );
+    }
+
+    #[test]
+    fn test_map_literal() {
+        assert_parses("{}", "{}");
+        assert_parses("{\"a\": 1, 2: \"b\"}", "{a: 1, 2: b}");
+        assert_parses(
+            "{\"k\": [1, 2], \"m\": {1: 2},}",
+            "{k: [1 2], m: {1: 2}}",
+        );
+        assert_parses("m[\"a\"] = m[\"b\"]", "(= ([] m a) ([] m b))");
+    }
+
+    #[test]
+    fn test_map_keeps_insertion_order() {
+        assert_parses("{3: a, 1: b, 2: c}", "{3: a, 1: b, 2: c}");
+    }
+
+    #[test]
+    fn test_brace_at_statement_start() {
+        let statements = parse("{}\n{ print 1; }\n{\"a\": 1};\n{x: 2}[\"x\"];");
+
+        assert!(matches!(statements[0], Stmt::Block(_)));
+        assert!(matches!(statements[1], Stmt::Block(_)));
+        match (&statements[2], &statements[3]) {
+            (Stmt::Expression(map), Stmt::Expression(index)) => {
+                assert!(matches!(map.expression, Expr::Map(_)));
+                assert!(matches!(index.expression, Expr::Index(_)));
+            }
+            stmts => panic!("expected expression statements, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_map_missing_colon() {
+        let errors = parse_errors("var m = {\"a\" 1};");
+
+        assert_eq!(errors[0].message, "Expect ':' after map key.");
+    }
+
+    #[test]
+    fn test_break_and_continue_in_loops() {
+        let statements = parse(
+            "while (true) { if (x) break; continue; }\nfor (;;) { { break; } }",
+        );
+
+        assert_eq!(statements.len(), 2);
+    }
+
+    #[test]
+    fn test_break_outside_loop() {
+        let errors = parse_errors("break;\nif (x) continue;\nprint 1;");
+
+        let messages: Vec<&str> =
+            errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Can't use 'break' outside of a loop.",
+                "Can't use 'continue' outside of a loop.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_break_inside_function_inside_loop() {
+        let errors = parse_errors(
+            "while (true) { fun f() { break; } var g = fun () { continue; }; }",
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].token.lexeme, "break");
+        assert_eq!(errors[1].token.lexeme, "continue");
+    }
+
+    #[test]
+    fn test_for_keeps_increment_for_continue() {
+        let statements = parse("for (var i = 0; i < 3; i = i + 1) continue;");
+
+        let Stmt::Block(block) = &statements[0] else {
+            panic!("expected block, got {:?}", statements[0]);
+        };
+        let Stmt::While(while_stmt) = &block.statements[1] else {
+            panic!("expected while, got {:?}", block.statements[1]);
+        };
+        assert!(matches!(*while_stmt.body, Stmt::Continue(_)));
+        assert_eq!(
+            while_stmt.increment.as_ref().map(show).as_deref(),
+            Some("(= i (+ i 1))")
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        assert_parses("a += 1", "(+= a 1)");
+        assert_parses("a -= b *= 2", "(-= a (*= b 2))");
+        assert_parses("o.x /= 2", "(/= (. o x) 2)");
+        assert_parses("xs[f()] %= 3", "(%= ([] xs (call f )) 3)");
+        assert_parses("a += b ? 1 : 2", "(+= a (?: b 1 2))");
+    }
+
+    #[test]
+    fn test_increment_decrement() {
+        assert_parses("++i", "(++pre i)");
+        assert_parses("i--", "(--post i)");
+        assert_parses("-x++", "(- (++post x))");
+        assert_parses("++o.count", "(++pre (. o count))");
+        assert_parses("xs[i]++ + 1", "(+ (++post ([] xs i)) 1)");
+    }
+
+    #[test]
+    fn test_invalid_update_targets() {
+        let error = parse_expr("a + b += 1").err().unwrap();
+        assert_eq!(error.message, "Invalid assignment target.");
+
+        let error = parse_expr("++f()").err().unwrap();
+        assert_eq!(error.message, "Invalid '++' operand.");
+
+        let error = parse_expr("1--").err().unwrap();
+        assert_eq!(error.message, "Invalid '--' operand.");
+    }
+
+    #[test]
+    fn test_modulo_and_integer_division() {
+        assert_parses("a % b * c", "(* (% a b) c)");
+        assert_parses("7 ~/ 2 + 1", "(+ (~/ 7 2) 1)");
+        assert_parses("a - b % c", "(- a (% b c))");
+    }
+
+    #[test]
+    fn test_exponent() {
+        assert_parses("2 ** 3 ** 2", "(** 2 (** 3 2))");
+        assert_parses("-2 ** 2", "(- (** 2 2))");
+        assert_parses("2 ** -1", "(** 2 (- 1))");
+        assert_parses("2 * 3 ** 2", "(* 2 (** 3 2))");
+        assert_parses("a.b ** f(2)", "(** (. a b) (call f 2))");
+        assert_parses("++i ** 2", "(** (++pre i) 2)");
+    }
+
+    #[test]
+    fn test_integer_and_float_literals() {
+        assert_parses("1 + 2.5", "(+ 1 2.5)");
+        assert_parses("3.0", "3.0");
+        assert_parses("9007199254740993", "9007199254740993");
+        assert_parses("9223372036854775807", "9223372036854775807");
+    }
+
+    #[test]
+    fn test_integer_literal_too_large() {
+        let error = parse_expr("9223372036854775808").err().unwrap();
+
+        assert_eq!(error.message, "Integer literal is too large.");
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        assert_parses("a | b ^ c & d", "(| a (^ b (& c d)))");
+        assert_parses("1 << 2 + 3", "(<< 1 (+ 2 3))");
+        assert_parses("x & 1 == 0", "(== (& x 1) 0)");
+        assert_parses("a >> 1 >> 2", "(>> (>> a 1) 2)");
+        assert_parses("~x & ~-y", "(& (~ x) (~ (- y)))");
+        assert_parses("a < b | c", "(< a (| b c))");
+    }
+
+    fn match_stmt(source: &str) -> (Match, Vec<ParserError>) {
+        let (result, warnings) = parse_with(source, |parser| parser);
+        let mut statements = match result {
+            Ok(statements) => statements,
+            Err(errors) => panic!("parse failed: {:?}", errors),
+        };
+        match statements.remove(0) {
+            Stmt::Match(stmt) => (stmt, warnings),
+            stmt => panic!("expected match, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_match_statement() {
+        let (stmt, warnings) = match_stmt(
+            "match (value) {\n  1 => print \"one\";\n  \"x\" | \"y\" => { print 2; },\n  [a, [b, _]] if a > b => print a;\n  Point { x, y: 0 } => print x;\n  -2.5 => print 3;\n  _ => print 4;\n}",
+        );
+
+        assert!(warnings.is_empty());
+        assert_eq!(stmt.patterns.len(), 6);
+        assert!(matches!(
+            &stmt.patterns[0],
+            Pattern::Literal(p) if p.value == LiteralValue::Integer(1)
+        ));
+        match &stmt.patterns[1] {
+            Pattern::Alternation(p) => assert_eq!(p.alternatives.len(), 2),
+            pattern => panic!("expected alternation, got {:?}", pattern),
+        }
+        match &stmt.patterns[2] {
+            Pattern::List(p) => {
+                assert!(matches!(p.elements[0], Pattern::Binding(_)));
+                assert!(matches!(p.elements[1], Pattern::List(_)));
+            }
+            pattern => panic!("expected list pattern, got {:?}", pattern),
+        }
+        assert!(stmt.guards[2].is_some());
+        match &stmt.patterns[3] {
+            Pattern::Instance(p) => {
+                assert_eq!(p.class_name.lexeme, "Point");
+                assert_eq!(p.fields.len(), 2);
+                assert!(matches!(p.patterns[0], Pattern::Binding(_)));
+                assert!(matches!(p.patterns[1], Pattern::Literal(_)));
+            }
+            pattern => panic!("expected instance pattern, got {:?}", pattern),
+        }
+        assert!(matches!(
+            &stmt.patterns[4],
+            Pattern::Literal(p) if p.value == LiteralValue::Number(-2.5)
+        ));
+        assert!(matches!(stmt.patterns[5], Pattern::Wildcard(_)));
+        assert!(matches!(stmt.bodies[1], Stmt::Block(_)));
+    }
+
+    #[test]
+    fn test_unreachable_match_arm() {
+        let (_, warnings) = match_stmt(
+            "match (x) { 1 => print 1; n => print n; 2 => print 2; }",
+        );
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Unreachable match arm after wildcard pattern."
+        );
+        assert_eq!(warnings[0].token.lexeme, "2");
+    }
+
+    #[test]
+    fn test_alternation_with_wildcard_is_catch_all() {
+        let (_, warnings) = match_stmt(
+            "match (x) { 1 | _ => print 1; 2 => print 2; 3 | n => print 3; }",
+        );
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].token.lexeme, "2");
+        assert_eq!(warnings[1].token.lexeme, "3");
+
+        let (_, warnings) =
+            match_stmt("match (x) { 1 | 2 => print 1; _ => print 2; }");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_guarded_wildcard_is_not_catch_all() {
+        let (_, warnings) =
+            match_stmt("match (x) { _ if x > 1 => print 1; _ => print 2; }");
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_match_errors() {
+        let errors = parse_errors("match (x) { 1 print 1; }");
+        assert_eq!(errors[0].message, "Expect '=>' after pattern.");
+
+        let errors = parse_errors("match (x) { a.b => print 1; }");
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected one of `|`, `if` or `=>`, found `.`"
+        );
+
+        let errors = parse_errors("match (x) { + => print 1; }");
+        assert_eq!(errors[0].message, "Expect pattern.");
+    }
+
+    fn expression_stmt(source: &str) -> String {
+        match parse(source).remove(0) {
+            Stmt::Expression(stmt) => show(&stmt.expression),
+            stmt => panic!("expected expression, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_destructuring_declaration() {
+        let statements = parse(
+            "var [a, [b, _], ...rest] = xs;\nvar {x, y: [first]} = point;",
+        );
+
+        match &statements[0] {
+            Stmt::VarPattern(stmt) => {
+                assert_eq!(show_pattern(&stmt.pattern), "[a [b _] ...rest]");
+                assert_eq!(show(&stmt.initializer), "xs");
+            }
+            stmt => panic!("expected var pattern, got {:?}", stmt),
+        }
+        match &statements[1] {
+            Stmt::VarPattern(stmt) => {
+                assert_eq!(show_pattern(&stmt.pattern), "{x: x, y: [first]}");
+            }
+            stmt => panic!("expected var pattern, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_destructuring_assignment() {
+        assert_eq!(expression_stmt("[a, b] = [b, a];"), "(= [a b] [b a])");
+        assert_eq!(
+            expression_stmt("{x, y} = {\"x\": 1, \"y\": 2};"),
+            "(= {x: x, y: y} {x: 1, y: 2})"
+        );
+        assert_eq!(expression_stmt("[a, b][0] = 1;"), "(= ([] [a b] 0) 1)");
+        assert!(matches!(parse("{x;}")[0], Stmt::Block(_)));
+    }
+
+    #[test]
+    fn test_destructuring_errors() {
+        let errors = parse_errors("var [a, b];");
+        assert_eq!(
+            errors[0].message,
+            "Destructuring declaration requires an initializer."
+        );
+
+        let errors = parse_errors("var [a, 1] = xs;");
+        assert_eq!(
+            errors[0].message,
+            "Only names, '_', list and object patterns can be destructured."
+        );
+        assert_eq!(errors[0].token.lexeme, "1");
+
+        let errors = parse_errors("var [...rest, a] = xs;");
+        assert_eq!(errors[0].message, "Expect ']' after list pattern.");
+    }
+
+    #[test]
+    fn test_default_and_rest_parameters() {
+        let statements =
+            parse("fun f(a, b = 2, ...rest) {}\nvar g = fun(x = 1) {};");
+
+        match &statements[0] {
+            Stmt::Function(function) => {
+                assert_eq!(function.params.len(), 2);
+                assert!(function.defaults[0].is_none());
+                assert_eq!(show(function.defaults[1].as_ref().unwrap()), "2");
+                assert_eq!(function.rest.as_ref().unwrap().lexeme, "rest");
+
+                let signature = Signature::from_params(
+                    &function.params,
+                    &function.defaults,
+                    function.rest.as_ref(),
+                );
+                assert_eq!(signature.min_arity(), 1);
+                assert_eq!(signature.max_arity(), None);
+            }
+            stmt => panic!("expected function, got {:?}", stmt),
+        }
+        match &statements[1] {
+            Stmt::Var(Var {
+                initializer: Some(Expr::Lambda(lambda)),
+                ..
+            }) => assert!(lambda.defaults[0].is_some()),
+            stmt => panic!("expected lambda, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_named_arguments() {
+        assert_parses("f(1, b: 3)", "(call f 1 b: 3)");
+        assert_parses("f(a ? b : c)", "(call f (?: a b c))");
+    }
+
+    #[test]
+    fn test_parameter_and_argument_errors() {
+        let errors = parse_errors("fun f(a = 1, b) {}");
+        assert_eq!(
+            errors[0].message,
+            "Parameter without default can't follow one with a default."
+        );
+
+        let errors = parse_errors("fun f(...rest, a) {}");
+        assert_eq!(errors[0].message, "Expect ')' after parameters.");
+
+        let errors = parse_errors("f(b: 1, 2);");
+        assert_eq!(
+            errors[0].message,
+            "Positional argument can't follow a named argument."
+        );
+
+        let errors = parse_errors("f(b: 1, b: 2);");
+        assert_eq!(errors[0].message, "Duplicate named argument.");
+    }
+
+    #[test]
+    fn test_import_and_export() {
+        let statements = parse(
+            "import \"lib/math.lox\" as math;\n/// Doubles.\nexport fun twice(x) { return x * 2; }\nexport var y = 1;",
+        );
+
+        match &statements[0] {
+            Stmt::Import(import) => {
+                assert_eq!(
+                    import.path.literal.as_deref(),
+                    Some("lib/math.lox")
+                );
+                assert_eq!(import.alias.lexeme, "math");
+            }
+            stmt => panic!("expected import, got {:?}", stmt),
+        }
+        match &statements[1] {
+            Stmt::Export(export) => match export.declaration.as_ref() {
+                Stmt::Function(function) => {
+                    assert_eq!(function.doc.as_deref(), Some("Doubles."))
+                }
+                stmt => panic!("expected function, got {:?}", stmt),
+            },
+            stmt => panic!("expected export, got {:?}", stmt),
+        }
+        assert!(matches!(statements[2], Stmt::Export(_)));
+    }
+
+    #[test]
+    fn test_import_export_errors() {
+        let errors = parse_errors("{ import \"a.lox\" as a; }");
+        assert_eq!(errors[0].message, "Can only import at the top level.");
+
+        let errors = parse_errors("fun f() { export var x; }");
+        assert_eq!(errors[0].message, "Can only export at the top level.");
+
+        let errors = parse_errors("export print 1;");
+        assert_eq!(errors[0].message, "Expect declaration after 'export'.");
+
+        let errors = parse_errors("import \"a.lox\";");
+        assert_eq!(errors[0].message, "Expect 'as' after module path.");
+    }
+
+    #[test]
+    fn test_try_catch_finally() {
+        let statements = parse(
+            "try { risky(); } catch (e) { print e.message; } finally { close(); }\ntry { } finally { }\nthrow \"boom\";",
+        );
+
+        match &statements[0] {
+            Stmt::Try(stmt) => {
+                assert_eq!(stmt.body.len(), 1);
+                assert_eq!(stmt.catch_name.as_ref().unwrap().lexeme, "e");
+                assert_eq!(stmt.catch_body.as_ref().unwrap().len(), 1);
+                assert_eq!(stmt.finally_body.as_ref().unwrap().len(), 1);
+            }
+            stmt => panic!("expected try, got {:?}", stmt),
+        }
+        match &statements[1] {
+            Stmt::Try(stmt) => {
+                assert!(stmt.catch_name.is_none());
+                assert!(stmt.finally_body.is_some());
+            }
+            stmt => panic!("expected try, got {:?}", stmt),
+        }
+        match &statements[2] {
+            Stmt::Throw(stmt) => assert_eq!(show(&stmt.value), "boom"),
+            stmt => panic!("expected throw, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let errors = parse_errors("try { }\nprint 1;");
+        assert_eq!(
+            errors[0].message,
+            "Expect 'catch' or 'finally' after try block."
+        );
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `catch` or `finally`, found `print`"
+        );
+
+        let errors = parse_errors("try { } catch { }");
+        assert_eq!(errors[0].message, "Expect '(' after 'catch'.");
+
+        let errors = parse_errors("throw;");
+        assert_eq!(errors[0].message, "Expect expression");
+    }
+
+    #[test]
+    fn test_class_members() {
+        let statements = parse(
+            "class Circle {\n  class count = 0;\n  class unit() { return Circle(1); }\n  /// The area.\n  area { return 3.14 * this.r * this.r; }\n  init(r) { this.r = r; }\n}",
+        );
+
+        match &statements[0] {
+            Stmt::Class(class) => {
+                assert_eq!(class.static_fields[0].lexeme, "count");
+                assert_eq!(show(&class.static_values[0]), "0");
+                assert_eq!(class.class_methods[0].name.lexeme, "unit");
+                assert_eq!(class.getters[0].name.lexeme, "area");
+                assert_eq!(class.getters[0].doc.as_deref(), Some("The area."));
+                assert_eq!(class.getters[0].body.len(), 1);
+                assert_eq!(class.methods[0].name.lexeme, "init");
+                assert_eq!(class.methods[0].params.len(), 1);
+            }
+            stmt => panic!("expected class, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_class_member_errors() {
+        let errors = parse_errors("class A { class x; }");
+        assert_eq!(
+            errors[0].message,
+            "Expect '(' or '=' after class member name."
+        );
+
+        let errors = parse_errors("class A { x; }");
+        assert_eq!(
+            errors[0].expected_found().unwrap(),
+            "expected `{` or `(`, found `;`"
+        );
+
+        let errors = parse_errors("while (x) { class A { get { break; } } }");
+        assert_eq!(errors[0].message, "Can't use 'break' outside of a loop.");
+    }
+
+    #[test]
+    fn test_trait_declaration_and_with() {
+        let statements = parse(
+            "/// Can be compared.\ntrait Ord { less(other) {} }\ntrait Show { show() {} }\nclass Money < Value with Ord, Show { init(n) {} }",
+        );
+
+        match &statements[0] {
+            Stmt::Trait(stmt) => {
+                assert_eq!(stmt.name.lexeme, "Ord");
+                assert_eq!(stmt.doc.as_deref(), Some("Can be compared."));
+                assert_eq!(stmt.methods[0].name.lexeme, "less");
+            }
+            stmt => panic!("expected trait, got {:?}", stmt),
+        }
+        match &statements[2] {
+            Stmt::Class(class) => {
+                let traits: Vec<String> =
+                    class.traits.iter().map(show).collect();
+                assert_eq!(traits, ["Ord", "Show"]);
+                assert!(class.superclass.is_some());
+            }
+            stmt => panic!("expected class, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_trait_conflicts() {
+        let errors = parse_errors(
+            "trait A { f() {} g() {} h() {} }\ntrait B { g() {} f() {} }\ntrait C { h() {} }\nclass X with A, B, C { h() {} }",
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "Conflicting trait methods in class 'X': 'f' (A, B), 'g' (A, B)."
+        );
+        assert_eq!(errors[0].token.lexeme, "X");
+
+        // 게터, 클래스 메서드, 클래스 필드도 충돌을 해결한다
+        for member in ["f {}", "class f() {}", "class f = 1;"] {
+            let source = format!(
+                "trait T {{ f() {{}} }}\ntrait U {{ f() {{}} }}\nclass C with T, U {{ {} }}",
+                member
+            );
+            parse(&source);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_trait() {
+        let errors =
+            parse_errors("trait T { f() {} }\nclass C with T, U, T {}");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Duplicate trait 'T' in class 'C'.");
+        assert_eq!(errors[0].token.span.start, 38);
+    }
+
+    #[test]
+    fn test_optional_chaining() {
+        assert_parses("a?.b", "(chain (?. a b))");
+        assert_parses("a?.b?.c()", "(chain (call (?. (?. a b) c) ))");
+        assert_parses("a?.b.c[0] + 1", "(+ (chain ([] (. (?. a b) c) 0)) 1)");
+        assert_parses("f(a?.b).c", "(. (call f (chain (?. a b))) c)");
+        assert_parses("a.b?.c", "(chain (?. (. a b) c))");
+    }
+
+    #[test]
+    fn test_nil_coalescing() {
+        assert_parses("a ?? b ?? c", "(?? a (?? b c))");
+        assert_parses("a ?? b or c", "(?? a (or b c))");
+        assert_parses("x ? a ?? b : c", "(?: x (?? a b) c)");
+        assert_parses("a?.b ?? 0", "(?? (chain (?. a b)) 0)");
+    }
+
+    #[test]
+    fn test_optional_chain_errors() {
+        let error = parse_expr("a?.b = 1").err().unwrap();
+        assert_eq!(error.message, "Invalid assignment target.");
+
+        let error = parse_expr("a?.1").err().unwrap();
+        assert_eq!(error.message, "Expect property name after '?.'.");
+    }
+
+    fn parse_errors_with_depth(
+        source: &str,
+        max_depth: usize,
+    ) -> Vec<ParserError> {
+        let tokens = scan(source);
+        let mut parser = Parser::new(tokens.clone()).with_max_depth(max_depth);
+        let errors = match parser.parse() {
+            Ok(statements) => panic!("expected errors, got {:?}", statements),
+            Err(errors) => errors,
+        };
+
+        let parser = Parser::new(tokens).with_max_depth(max_depth);
+        let cst = cst::parse(source, parser);
+        assert_eq!(cst.root.text(), source);
+        assert_eq!(format!("{:?}", cst.errors), format!("{:?}", errors));
+        errors
+    }
+
+    #[test]
+    fn test_deeply_nested_expression() {
+        let source =
+            format!("{}1{};", "(".repeat(100_000), ")".repeat(100_000));
+        let errors = parse_errors(&source);
+
+        assert_eq!(errors[0].message, "Expression nested too deeply");
+        assert_eq!(errors[0].token.lexeme, "(");
+    }
+
+    #[test]
+    fn test_deeply_nested_unary_list_and_block() {
+        let source = format!("print {}1;", "-".repeat(100_000));
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Expression nested too deeply"
+        );
+
+        let source = format!("var x = {}];", "[".repeat(100_000));
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Expression nested too deeply"
+        );
+
+        let source = "{".repeat(100_000);
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Statement nested too deeply"
+        );
+
+        // 한도에 닿는 곳이 조건식이다
+        let source = "if (x) ".repeat(100_000);
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Expression nested too deeply"
+        );
+
+        let source = "class A { m() { ".repeat(100_000);
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Statement nested too deeply"
+        );
+
+        let source = format!("var {} = x;", "[".repeat(100_000));
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Pattern nested too deeply"
+        );
+    }
+
+    #[test]
+    fn test_long_left_nested_chain() {
+        let source = format!("print 1{};", "+1".repeat(100_000));
+        assert_eq!(parse_errors(&source)[0].message, "Expression too long");
+
+        let source = format!("print a{};", ".b".repeat(100_000));
+        assert_eq!(parse_errors(&source)[0].message, "Expression too long");
+
+        // 재귀 한도(128)와 상관없이 길게 이어 쓸 수 있다
+        parse(&format!("print \"s\"{};", "+\"s\"".repeat(129)));
+        parse(&format!("print x{};", ".f()".repeat(65)));
+        parse(&format!("print 1{};", "+1".repeat(1000)));
+        parse(&format!(
+            "{}print 1{};{}",
+            "if (a) {".repeat(60),
+            "+1".repeat(1000),
+            "}".repeat(60)
+        ));
+
+        // 괄호 안의 chain도 바깥 chain의 높이에 더해진다
+        let group = format!("(1{})", "+1".repeat(600));
+        let source = format!("print 1{}+{};", "+1".repeat(600), group);
+        parse(&source);
+        let source = format!("print {}{};", group, "+1".repeat(600));
+        assert_eq!(parse_errors(&source)[0].message, "Expression too long");
+    }
+
+    #[test]
+    fn test_effective_nesting_limits() {
+        // if 문과 블록이 한 칸씩
+        let n = 60;
+        parse(&format!("{}x;{}", "if (a) {".repeat(n), "}".repeat(n)));
+        // 람다 식, 본문, return이 한 칸씩
+        let n = 40;
+        parse(&format!(
+            "var f = {}1{};",
+            "fun () { return ".repeat(n),
+            "; }".repeat(n)
+        ));
+        let n = 120;
+        parse(&format!("{}x;", "for (;;) ".repeat(n)));
+        parse(&format!("{}x;{}", "{".repeat(n), "}".repeat(n)));
+
+        // 한도에 닿는 곳이 조건식이다
+        let chain = "else if (a) x; ".repeat(100_000);
+        let source = format!("if (a) x; {}", chain);
+        assert_eq!(
+            parse_errors(&source)[0].message,
+            "Expression nested too deeply"
+        );
+    }
+
+    #[test]
+    fn test_configurable_max_depth() {
+        let source = "print ((((1))));";
+        parse(source);
+
+        let errors = parse_errors_with_depth(source, 4);
+        assert_eq!(errors[0].message, "Expression nested too deeply");
+
+        let nested = format!("print {}1{};", "(".repeat(120), ")".repeat(120));
+        parse(&nested);
+    }
+
+    fn asi(parser: Parser) -> Parser {
+        parser.with_automatic_semicolons(true)
+    }
+
+    fn parse_asi(source: &str) -> Vec<Stmt> {
+        match parse_with(source, asi).0 {
+            Ok(statements) => statements,
+            Err(errors) => panic!("parse failed: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn test_automatic_semicolons() {
+        let statements = parse_asi("var a = 1\nprint a\n{ a = 2 }\nf(a)");
+
+        assert_eq!(statements.len(), 4);
+        assert!(matches!(statements[2], Stmt::Block(_)));
+
+        // 줄이 바뀌어도 식이 이어질 수 있으면 이어서 읽는다
+        let statements = parse_asi("var a = b\n  .c\n  (1)");
+        match &statements[0] {
+            Stmt::Var(var) => {
+                assert_eq!(
+                    show(var.initializer.as_ref().unwrap()),
+                    "(call (. b c) 1)"
+                )
+            }
+            stmt => panic!("expected var, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_return_before_newline() {
+        let statements = parse_asi("fun f() {\n  return\n  1\n}");
+
+        match &statements[0] {
+            Stmt::Function(function) => {
+                assert!(
+                    matches!(&function.body[0], Stmt::Return(r) if r.value.is_none())
+                );
+                assert_eq!(function.body.len(), 2);
+            }
+            stmt => panic!("expected function, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_update_on_next_line_is_prefix() {
+        let statements = parse_asi("var a = 1\n++a\na\n--\nb");
+
+        assert_eq!(statements.len(), 4);
+        match (&statements[1], &statements[2]) {
+            (Stmt::Expression(first), Stmt::Expression(second)) => {
+                assert_eq!(show(&first.expression), "(++pre a)");
+                assert_eq!(show(&second.expression), "a");
+            }
+            stmts => panic!("expected expressions, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_no_semicolon_inserted_in_for_header() {
+        parse_asi("for (var i = 0;\n  i < 3;\n  i++) print i");
+
+        for source in [
+            "for (var i = 0\n i < 3; i++) print i;",
+            "for (i = 0\n i < 3; i++) print i;",
+        ] {
+            let errors = parse_with(source, asi).0.unwrap_err();
+
+            assert!(errors[0].message.starts_with("Expect ';' after"));
+            assert_eq!(errors[0].token.lexeme, "i");
+        }
+    }
+
+    #[test]
+    fn test_semicolons_required_by_default() {
+        let errors = parse_errors("var a = 1\nprint a;");
+
+        assert_eq!(errors[0].message, "Expect ';' after variable declaration.");
     }
 }
@@ -0,0 +1,222 @@
+use crate::{expr::Expr, token_type::TokenType};
+
+use super::{Parser, ParserError};
+
+// 낮은 것부터 높은 것 순서 (derive(PartialOrd)가 선언 순서를 따른다)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    None,
+    Comma,       // ,
+    Assignment,  // =
+    Conditional, // ?:
+    Coalesce,    // ??
+    Or,          // or
+    And,         // and
+    Equality,    // == !=
+    Comparison,  // < > <= >=
+    BitOr,       // |
+    BitXor,      // ^
+    BitAnd,      // &
+    Shift,       // << >>
+    Term,        // + -
+    Factor,      // * / % ~/
+    Unary,       // ! - ~ ++ --
+    Exponent,    // **
+    Call,        // . ?. () [] 후위 ++ --
+    Primary,
+}
+
+impl Precedence {
+    pub fn next(self) -> Self {
+        match self {
+            Precedence::None => Precedence::Comma,
+            Precedence::Comma => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Conditional,
+            Precedence::Conditional => Precedence::Coalesce,
+            Precedence::Coalesce => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::BitOr,
+            Precedence::BitOr => Precedence::BitXor,
+            Precedence::BitXor => Precedence::BitAnd,
+            Precedence::BitAnd => Precedence::Shift,
+            Precedence::Shift => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor => Precedence::Unary,
+            Precedence::Unary => Precedence::Exponent,
+            Precedence::Exponent => Precedence::Call,
+            Precedence::Call | Precedence::Primary => Precedence::Primary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+// prefix는 토큰을 소비하기 전에, infix는 연산자를 소비한 뒤에 호출된다
+pub type PrefixFn = fn(&mut Parser) -> Result<Expr, ParserError>;
+pub type InfixFn = fn(&mut Parser, Expr) -> Result<Expr, ParserError>;
+
+pub struct ParseRule {
+    pub prefix: Option<PrefixFn>,
+    pub infix: Option<InfixFn>,
+    pub precedence: Precedence,
+    pub associativity: Associativity,
+}
+
+impl ParseRule {
+    const NONE: ParseRule = ParseRule {
+        prefix: None,
+        infix: None,
+        precedence: Precedence::None,
+        associativity: Associativity::Left,
+    };
+
+    fn prefix(prefix: PrefixFn) -> Self {
+        Self {
+            prefix: Some(prefix),
+            ..Self::NONE
+        }
+    }
+
+    fn infix(
+        infix: InfixFn,
+        precedence: Precedence,
+        associativity: Associativity,
+    ) -> Self {
+        Self {
+            infix: Some(infix),
+            precedence,
+            associativity,
+            ..Self::NONE
+        }
+    }
+
+    fn with_prefix(self, prefix: PrefixFn) -> Self {
+        Self {
+            prefix: Some(prefix),
+            ..self
+        }
+    }
+}
+
+// 연산자 추가는 여기에 한 줄 추가하면 된다
+pub fn rule(token_type: TokenType) -> ParseRule {
+    use Associativity::{Left, Right};
+
+    match token_type {
+        TokenType::LeftParen => {
+            ParseRule::infix(Parser::call, Precedence::Call, Left)
+                .with_prefix(Parser::primary)
+        }
+        TokenType::LeftBracket => {
+            ParseRule::infix(Parser::index, Precedence::Call, Left)
+                .with_prefix(Parser::list)
+        }
+        TokenType::LeftBrace => ParseRule::prefix(Parser::map),
+        TokenType::Comma => {
+            ParseRule::infix(Parser::comma, Precedence::Comma, Left)
+        }
+        TokenType::Dot => ParseRule::infix(Parser::get, Precedence::Call, Left),
+        TokenType::QuestionDot => {
+            ParseRule::infix(Parser::optional_get, Precedence::Call, Left)
+        }
+        // or보다 약하게 묶인다: a ?? b or c == a ?? (b or c)
+        TokenType::QuestionQuestion => {
+            ParseRule::infix(Parser::coalesce, Precedence::Coalesce, Right)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Minus => {
+            ParseRule::infix(Parser::binary, Precedence::Term, Left)
+                .with_prefix(Parser::unary)
+        }
+        TokenType::Plus => {
+            ParseRule::infix(Parser::binary, Precedence::Term, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Slash
+        | TokenType::Star
+        | TokenType::Percent
+        | TokenType::TildeSlash => {
+            ParseRule::infix(Parser::binary, Precedence::Factor, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        // 단항 -보다 강하게 묶인다: -2 ** 2 == -(2 ** 2)
+        TokenType::StarStar => {
+            ParseRule::infix(Parser::binary, Precedence::Exponent, Right)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Bang | TokenType::Tilde => ParseRule::prefix(Parser::unary),
+        // 비트 연산은 Python처럼 비교보다 강하게 묶인다: x & 1 == 0
+        TokenType::Pipe => {
+            ParseRule::infix(Parser::binary, Precedence::BitOr, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Caret => {
+            ParseRule::infix(Parser::binary, Precedence::BitXor, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Ampersand => {
+            ParseRule::infix(Parser::binary, Precedence::BitAnd, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::LessLess | TokenType::GreaterGreater => {
+            ParseRule::infix(Parser::binary, Precedence::Shift, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::BangEqual | TokenType::EqualEqual => {
+            ParseRule::infix(Parser::binary, Precedence::Equality, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Greater
+        | TokenType::GreaterEqual
+        | TokenType::Less
+        | TokenType::LessEqual => {
+            ParseRule::infix(Parser::binary, Precedence::Comparison, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Equal => {
+            ParseRule::infix(Parser::assignment, Precedence::Assignment, Right)
+        }
+        TokenType::PlusEqual
+        | TokenType::MinusEqual
+        | TokenType::StarEqual
+        | TokenType::SlashEqual
+        | TokenType::PercentEqual => ParseRule::infix(
+            Parser::compound_assignment,
+            Precedence::Assignment,
+            Right,
+        ),
+        TokenType::PlusPlus | TokenType::MinusMinus => {
+            ParseRule::infix(Parser::postfix_update, Precedence::Call, Left)
+                .with_prefix(Parser::prefix_update)
+        }
+        TokenType::Question => ParseRule::infix(
+            Parser::conditional,
+            Precedence::Conditional,
+            Right,
+        ),
+        TokenType::And => {
+            ParseRule::infix(Parser::logical, Precedence::And, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Or => {
+            ParseRule::infix(Parser::logical, Precedence::Or, Left)
+                .with_prefix(Parser::missing_left_operand)
+        }
+        TokenType::Fun => ParseRule::prefix(Parser::lambda),
+        TokenType::Identifier
+        | TokenType::String
+        | TokenType::Number
+        | TokenType::False
+        | TokenType::True
+        | TokenType::Nil
+        | TokenType::This
+        | TokenType::Super => ParseRule::prefix(Parser::primary),
+        _ => ParseRule::NONE,
+    }
+}
@@ -0,0 +1,59 @@
+// Auto-generated by tools/generate_ast.rs
+// Do not edit manually
+
+use crate::expr::LiteralValue;
+use crate::token::Token;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Alternation(Alternation),
+    Binding(Binding),
+    Instance(Instance),
+    List(List),
+    Literal(Literal),
+    Object(Object),
+    Wildcard(Wildcard),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternation {
+    pub alternatives: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub name: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub class_name: Token,
+    pub fields: Vec<Token>,
+    pub patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct List {
+    pub bracket: Token,
+    pub elements: Vec<Pattern>,
+    pub rest: Option<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Literal {
+    pub token: Token,
+    pub value: LiteralValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub brace: Token,
+    pub fields: Vec<Token>,
+    pub patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wildcard {
+    pub token: Token,
+}
+
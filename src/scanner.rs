@@ -1,47 +1,75 @@
 use std::collections::HashMap;
 
-use crate::{token::Token, token_type::TokenType};
+use crate::{
+    source_map::{FileId, Span},
+    token::Token,
+    token_type::TokenType,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub message: String,
+    pub line: i32,
+    pub file: FileId,
+    pub span: Span,
+}
 
 pub struct Scanner {
-    source: String,
     tokens: Vec<Token>,
     chars: Vec<char>,
     start: usize,
     current: usize,
     line: i32,
-    had_error: bool,
+    file: FileId,
+    errors: Vec<ScanError>,
     keywords: HashMap<String, TokenType>,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Self {
+        Self::with_file(source, FileId::default())
+    }
+
+    pub fn with_file(source: String, file: FileId) -> Self {
         let chars = source.chars().collect();
         // ✅ HashMap 초기화
         let mut keywords = HashMap::new();
         keywords.insert("and".to_string(), TokenType::And);
+        keywords.insert("as".to_string(), TokenType::As);
+        keywords.insert("break".to_string(), TokenType::Break);
+        keywords.insert("catch".to_string(), TokenType::Catch);
         keywords.insert("class".to_string(), TokenType::Class);
+        keywords.insert("continue".to_string(), TokenType::Continue);
         keywords.insert("else".to_string(), TokenType::Else);
+        keywords.insert("export".to_string(), TokenType::Export);
         keywords.insert("false".to_string(), TokenType::False);
+        keywords.insert("finally".to_string(), TokenType::Finally);
         keywords.insert("for".to_string(), TokenType::For);
         keywords.insert("fun".to_string(), TokenType::Fun);
         keywords.insert("if".to_string(), TokenType::If);
+        keywords.insert("import".to_string(), TokenType::Import);
+        keywords.insert("match".to_string(), TokenType::Match);
         keywords.insert("nil".to_string(), TokenType::Nil);
         keywords.insert("or".to_string(), TokenType::Or);
         keywords.insert("print".to_string(), TokenType::Print);
         keywords.insert("return".to_string(), TokenType::Return);
         keywords.insert("super".to_string(), TokenType::Super);
         keywords.insert("this".to_string(), TokenType::This);
+        keywords.insert("throw".to_string(), TokenType::Throw);
+        keywords.insert("trait".to_string(), TokenType::Trait);
         keywords.insert("true".to_string(), TokenType::True);
+        keywords.insert("try".to_string(), TokenType::Try);
         keywords.insert("var".to_string(), TokenType::Var);
         keywords.insert("while".to_string(), TokenType::While);
+        keywords.insert("with".to_string(), TokenType::With);
         Scanner {
-            source,
             tokens: Vec::new(),
             chars,
             start: 0,
             current: 0,
             line: 1,
-            had_error: false,
+            file,
+            errors: Vec::new(),
             keywords,
         }
     }
@@ -56,13 +84,48 @@ impl Scanner {
             lexeme: String::new(),
             literal: None,
             line: self.line,
+            file: self.file,
+            span: Span::new(self.current, self.current),
         });
 
         &self.tokens
     }
 
+    // offset/line 위치에서 이어서 스캔하는 scanner (incremental re-lexing용)
+    pub fn starting_at(
+        source: String,
+        file: FileId,
+        offset: usize,
+        line: i32,
+    ) -> Self {
+        let mut scanner = Self::with_file(source, file);
+        scanner.current = offset;
+        scanner.line = line;
+        scanner
+    }
+
+    // 토큰 하나를 스캔해서 돌려준다. 끝에 도달하면 None (Eof는 만들지 않음)
+    pub fn next_token(&mut self) -> Option<Token> {
+        while !self.is_at_end() {
+            self.start = self.current;
+            self.scan_token();
+            if let Some(token) = self.tokens.pop() {
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    pub fn offset(&self) -> usize {
+        self.current
+    }
+
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len()
+        self.current >= self.chars.len()
     }
 
     fn scan_token(&mut self) {
@@ -73,12 +136,73 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.current += 2;
+                    self.add_token(TokenType::DotDotDot);
+                } else {
+                    self.add_token(TokenType::Dot);
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '?' => {
+                if self.match_char('.') {
+                    self.add_token(TokenType::QuestionDot);
+                } else if self.match_char('?') {
+                    self.add_token(TokenType::QuestionQuestion);
+                } else {
+                    self.add_token(TokenType::Question);
+                }
+            }
+            ':' => self.add_token(TokenType::Colon),
+            '-' => {
+                if self.match_char('=') {
+                    self.add_token(TokenType::MinusEqual);
+                } else if self.match_char('-') {
+                    self.add_token(TokenType::MinusMinus);
+                } else {
+                    self.add_token(TokenType::Minus);
+                }
+            }
+            '+' => {
+                if self.match_char('=') {
+                    self.add_token(TokenType::PlusEqual);
+                } else if self.match_char('+') {
+                    self.add_token(TokenType::PlusPlus);
+                } else {
+                    self.add_token(TokenType::Plus);
+                }
+            }
+            '*' => {
+                if self.match_char('=') {
+                    self.add_token(TokenType::StarEqual);
+                } else if self.match_char('*') {
+                    self.add_token(TokenType::StarStar);
+                } else {
+                    self.add_token(TokenType::Star);
+                }
+            }
+            '%' => {
+                if self.match_char('=') {
+                    self.add_token(TokenType::PercentEqual);
+                } else {
+                    self.add_token(TokenType::Percent);
+                }
+            }
+            // 정수 나눗셈 (//는 주석이라 Dart처럼 ~/를 쓴다)
+            '~' => {
+                if self.match_char('/') {
+                    self.add_token(TokenType::TildeSlash);
+                } else {
+                    self.add_token(TokenType::Tilde);
+                }
+            }
             '!' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::BangEqual);
@@ -89,6 +213,8 @@ impl Scanner {
             '=' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::EqualEqual);
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::FatArrow);
                 } else {
                     self.add_token(TokenType::Equal);
                 }
@@ -96,6 +222,8 @@ impl Scanner {
             '<' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::LessEqual);
+                } else if self.match_char('<') {
+                    self.add_token(TokenType::LessLess);
                 } else {
                     self.add_token(TokenType::Less);
                 }
@@ -103,16 +231,25 @@ impl Scanner {
             '>' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::GreaterEqual);
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::GreaterGreater);
                 } else {
                     self.add_token(TokenType::Greater);
                 }
             }
             '/' => {
                 if self.match_char('/') {
+                    // 문서 주석은 토큰으로 남긴다 (////는 일반 주석)
+                    let is_doc = self.peek() == '/' && self.peek_next() != '/';
                     // 주석: 줄 끝까지
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    if is_doc {
+                        self.scan_doc_comment();
+                    }
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
@@ -153,7 +290,7 @@ impl Scanner {
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
+        if self.current + 1 >= self.chars.len() {
             '\0'
         } else {
             self.chars[self.current + 1]
@@ -169,6 +306,11 @@ impl Scanner {
         }
     }
 
+    // chars 기준 offset이라 String을 byte로 자르면 안 된다
+    fn text(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().collect()
+    }
+
     fn add_token(&mut self, token_type: TokenType) {
         self.add_token_literal(token_type, None);
     }
@@ -178,12 +320,14 @@ impl Scanner {
         token_type: TokenType,
         literal: Option<String>,
     ) {
-        let text = &self.source[self.start..self.current];
+        let text = self.text(self.start, self.current);
         self.tokens.push(Token {
             token_type,
-            lexeme: text.to_string(),
+            lexeme: text,
             literal,
             line: self.line,
+            file: self.file,
+            span: Span::new(self.start, self.current),
         });
     }
 
@@ -201,8 +345,8 @@ impl Scanner {
             return;
         }
         self.advance();
-        let value = &self.source[self.start + 1..self.current - 1];
-        self.add_token_literal(TokenType::String, Some(value.to_string()));
+        let value = self.text(self.start + 1, self.current - 1);
+        self.add_token_literal(TokenType::String, Some(value));
     }
 
     //scan number
@@ -218,8 +362,15 @@ impl Scanner {
                 self.advance();
             }
         }
-        let value = &self.source[self.start..self.current];
-        self.add_token_literal(TokenType::Number, Some(value.to_string()));
+        let value = self.text(self.start, self.current);
+        self.add_token_literal(TokenType::Number, Some(value));
+    }
+
+    // doc comment: "/// text" -> "text"
+    fn scan_doc_comment(&mut self) {
+        let text = self.text(self.start + 3, self.current);
+        let text = text.strip_prefix(' ').unwrap_or(&text).trim_end();
+        self.add_token_literal(TokenType::DocComment, Some(text.to_string()));
     }
 
     //scan identifier
@@ -228,11 +379,11 @@ impl Scanner {
             self.advance();
         }
 
-        let text = &self.source[self.start..self.current];
+        let text = self.text(self.start, self.current);
 
         let token_type = self
             .keywords
-            .get(text)
+            .get(&text)
             .copied() // &TokenType -> TokenType
             .unwrap_or(TokenType::Identifier); // 없으면 Identifier
 
@@ -241,12 +392,20 @@ impl Scanner {
 
     // Error
     fn error(&mut self, line: i32, message: &str) {
-        eprintln!("[line {}] Error: {}", self.line, message);
-        self.had_error = true;
+        self.errors.push(ScanError {
+            message: message.to_string(),
+            line,
+            file: self.file,
+            span: Span::new(self.start, self.current),
+        });
     }
 
     pub fn had_error(&self) -> bool {
-        self.had_error
+        !self.errors.is_empty()
+    }
+
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
     }
 }
 
@@ -357,6 +516,16 @@ mod tests {
         assert_eq!(tokens[6].token_type, TokenType::Var);
     }
 
+    #[test]
+    fn test_loop_control_keywords() {
+        let source = "break continue".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Break);
+        assert_eq!(tokens[1].token_type, TokenType::Continue);
+    }
+
     #[test]
     fn test_identifiers() {
         let source = "myVar _test123 hello_world".to_string();
@@ -438,4 +607,212 @@ mod tests {
         assert_eq!(tokens[1].token_type, TokenType::False);
         assert_eq!(tokens[2].token_type, TokenType::Nil);
     }
+
+    #[test]
+    fn test_token_spans() {
+        let source = "var answer = 42;".to_string();
+        let mut scanner = Scanner::with_file(source, FileId(3));
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[1].span, Span::new(4, 10));
+        assert_eq!(tokens[3].span, Span::new(13, 15));
+        assert_eq!(tokens[3].file, FileId(3));
+        assert_eq!(tokens[5].span, Span::new(16, 16));
+    }
+
+    #[test]
+    fn test_errors_collected() {
+        let source = "var @ = \"oops".to_string();
+        let mut scanner = Scanner::new(source);
+        scanner.scan_tokens();
+
+        assert!(scanner.had_error());
+        assert_eq!(scanner.errors().len(), 2);
+        assert_eq!(scanner.errors()[0].message, "Unexpected character.");
+        assert_eq!(scanner.errors()[0].span, Span::new(4, 5));
+        assert_eq!(scanner.errors()[1].message, "Unterminated string.");
+    }
+
+    #[test]
+    fn test_doc_comment_non_ascii() {
+        let source = "/// 두 수를 더한다\nvar x = 1;".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].literal, Some("두 수를 더한다".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::Var);
+    }
+
+    #[test]
+    fn test_doc_comment() {
+        let source = "/// Adds two numbers.\n//// not docs\nfun".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].token_type, TokenType::DocComment);
+        assert_eq!(tokens[0].literal, Some("Adds two numbers.".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::Fun);
+        assert_eq!(tokens[1].line, 3);
+    }
+
+    #[test]
+    fn test_conditional_tokens() {
+        let source = "a ? b : c".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[1].token_type, TokenType::Question);
+        assert_eq!(tokens[3].token_type, TokenType::Colon);
+    }
+
+    #[test]
+    fn test_brackets() {
+        let source = "xs[0]".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[1].token_type, TokenType::LeftBracket);
+        assert_eq!(tokens[3].token_type, TokenType::RightBracket);
+    }
+
+    #[test]
+    fn test_compound_assignment_tokens() {
+        let source = "+= -= *= /= %= ++ -- + -".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::PlusEqual);
+        assert_eq!(tokens[1].token_type, TokenType::MinusEqual);
+        assert_eq!(tokens[2].token_type, TokenType::StarEqual);
+        assert_eq!(tokens[3].token_type, TokenType::SlashEqual);
+        assert_eq!(tokens[4].token_type, TokenType::PercentEqual);
+        assert_eq!(tokens[5].token_type, TokenType::PlusPlus);
+        assert_eq!(tokens[6].token_type, TokenType::MinusMinus);
+        assert_eq!(tokens[7].token_type, TokenType::Plus);
+        assert_eq!(tokens[8].token_type, TokenType::Minus);
+    }
+
+    #[test]
+    fn test_arithmetic_operator_tokens() {
+        let source = "% ~/ ** * //".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].token_type, TokenType::Percent);
+        assert_eq!(tokens[1].token_type, TokenType::TildeSlash);
+        assert_eq!(tokens[2].token_type, TokenType::StarStar);
+        assert_eq!(tokens[3].token_type, TokenType::Star);
+    }
+
+    #[test]
+    fn test_bitwise_tokens() {
+        let source = "& | ^ ~ << >> ~/ <= >=".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Ampersand);
+        assert_eq!(tokens[1].token_type, TokenType::Pipe);
+        assert_eq!(tokens[2].token_type, TokenType::Caret);
+        assert_eq!(tokens[3].token_type, TokenType::Tilde);
+        assert_eq!(tokens[4].token_type, TokenType::LessLess);
+        assert_eq!(tokens[5].token_type, TokenType::GreaterGreater);
+        assert_eq!(tokens[6].token_type, TokenType::TildeSlash);
+        assert_eq!(tokens[7].token_type, TokenType::LessEqual);
+        assert_eq!(tokens[8].token_type, TokenType::GreaterEqual);
+    }
+
+    #[test]
+    fn test_match_tokens() {
+        let source = "match (x) { _ => y }".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Match);
+        assert_eq!(tokens[5].token_type, TokenType::Identifier);
+        assert_eq!(tokens[5].lexeme, "_");
+        assert_eq!(tokens[6].token_type, TokenType::FatArrow);
+    }
+
+    #[test]
+    fn test_dot_dot_dot() {
+        let source = "[a, ...rest] .. .".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[3].token_type, TokenType::DotDotDot);
+        assert_eq!(tokens[4].lexeme, "rest");
+        assert_eq!(tokens[6].token_type, TokenType::Dot);
+        assert_eq!(tokens[7].token_type, TokenType::Dot);
+        assert_eq!(tokens[8].token_type, TokenType::Dot);
+    }
+
+    #[test]
+    fn test_module_keywords() {
+        let source = "import \"m.lox\" as m; export var x;".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[0].token_type, TokenType::Import);
+        assert_eq!(tokens[1].literal.as_deref(), Some("m.lox"));
+        assert_eq!(tokens[2].token_type, TokenType::As);
+        assert_eq!(tokens[5].token_type, TokenType::Export);
+    }
+
+    #[test]
+    fn test_exception_keywords() {
+        let source = "try catch finally throw trying".to_string();
+        let mut scanner = Scanner::new(source);
+        let types: Vec<TokenType> =
+            scanner.scan_tokens().iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Try,
+                TokenType::Catch,
+                TokenType::Finally,
+                TokenType::Throw,
+                TokenType::Identifier,
+                TokenType::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trait_keywords() {
+        let source = "class A < B with T {}".to_string();
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        assert_eq!(tokens[4].token_type, TokenType::With);
+
+        let mut scanner = Scanner::new("trait T {}".to_string());
+        assert_eq!(scanner.scan_tokens()[0].token_type, TokenType::Trait);
+    }
+
+    #[test]
+    fn test_optional_chaining_tokens() {
+        let source = "a?.b ?? c ? d : e".to_string();
+        let mut scanner = Scanner::new(source);
+        let types: Vec<TokenType> =
+            scanner.scan_tokens().iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Identifier,
+                TokenType::QuestionDot,
+                TokenType::Identifier,
+                TokenType::QuestionQuestion,
+                TokenType::Identifier,
+                TokenType::Question,
+                TokenType::Identifier,
+                TokenType::Colon,
+                TokenType::Identifier,
+                TokenType::Eof
+            ]
+        );
+    }
 }
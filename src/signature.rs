@@ -0,0 +1,201 @@
+use crate::{expr::Expr, token::Token};
+
+// 호출 가능한 것의 매개변수 모양. native 함수도 같은 방식으로 선언한다
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Signature {
+    pub params: Vec<String>,
+    pub required: usize,
+    pub rest: Option<String>,
+}
+
+// 매개변수 하나가 어디서 값을 받는지
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgSource {
+    Positional(usize),
+    Named(usize),
+    Default,
+}
+
+// bind의 결과. slots는 params와 같은 길이, rest는 남은 위치 인자의 index
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub slots: Vec<ArgSource>,
+    pub rest: Vec<usize>,
+}
+
+impl Signature {
+    pub fn new(required: &[&str]) -> Self {
+        Self {
+            params: required.iter().map(|p| p.to_string()).collect(),
+            required: required.len(),
+            rest: None,
+        }
+    }
+
+    pub fn with_optional(mut self, optional: &[&str]) -> Self {
+        self.params.extend(optional.iter().map(|p| p.to_string()));
+        self
+    }
+
+    pub fn with_rest(self, rest: &str) -> Self {
+        Self {
+            rest: Some(rest.to_string()),
+            ..self
+        }
+    }
+
+    // 파서가 만든 Function / Lambda의 매개변수에서
+    pub fn from_params(
+        params: &[Token],
+        defaults: &[Option<Expr>],
+        rest: Option<&Token>,
+    ) -> Self {
+        Self {
+            params: params.iter().map(|p| p.lexeme.clone()).collect(),
+            required: defaults.iter().take_while(|d| d.is_none()).count(),
+            rest: rest.map(|r| r.lexeme.clone()),
+        }
+    }
+
+    pub fn min_arity(&self) -> usize {
+        self.required
+    }
+
+    // ...rest가 있으면 상한이 없다
+    pub fn max_arity(&self) -> Option<usize> {
+        match self.rest {
+            Some(_) => None,
+            None => Some(self.params.len()),
+        }
+    }
+
+    // 위치 인자 개수와 이름 붙인 인자(호출 순서대로)를 매개변수에 맞춘다
+    pub fn bind(
+        &self,
+        positional: usize,
+        named: &[&str],
+    ) -> Result<Binding, String> {
+        if self.rest.is_none() && positional > self.params.len() {
+            return Err(self.arity_error(positional + named.len()));
+        }
+
+        let mut slots: Vec<Option<ArgSource>> = (0..self.params.len())
+            .map(|i| (i < positional).then_some(ArgSource::Positional(i)))
+            .collect();
+
+        for (i, name) in named.iter().enumerate() {
+            let index = self
+                .params
+                .iter()
+                .position(|p| p == name)
+                .ok_or_else(|| format!("Unknown parameter '{}'.", name))?;
+            if slots[index].is_some() {
+                return Err(format!(
+                    "Argument '{}' given more than once.",
+                    name
+                ));
+            }
+            slots[index] = Some(ArgSource::Named(i));
+        }
+
+        let mut bound = Vec::new();
+        for (i, slot) in slots.into_iter().enumerate() {
+            match slot {
+                Some(source) => bound.push(source),
+                None if i >= self.required => bound.push(ArgSource::Default),
+                None => {
+                    return Err(format!(
+                        "Missing argument for '{}'.",
+                        self.params[i]
+                    ))
+                }
+            }
+        }
+
+        Ok(Binding {
+            slots: bound,
+            rest: (self.params.len()..positional.max(self.params.len()))
+                .collect(),
+        })
+    }
+
+    fn arity_error(&self, got: usize) -> String {
+        match self.max_arity() {
+            Some(max) if max == self.required => {
+                format!("Expected {} arguments but got {}.", max, got)
+            }
+            Some(max) => format!(
+                "Expected {} to {} arguments but got {}.",
+                self.required, max, got
+            ),
+            None => format!(
+                "Expected at least {} arguments but got {}.",
+                self.required, got
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature() -> Signature {
+        Signature::new(&["a"]).with_optional(&["b", "c"])
+    }
+
+    #[test]
+    fn test_arity() {
+        let sig = signature();
+        assert_eq!(sig.min_arity(), 1);
+        assert_eq!(sig.max_arity(), Some(3));
+        assert_eq!(sig.with_rest("xs").max_arity(), None);
+    }
+
+    #[test]
+    fn test_bind_positional_and_named() {
+        let binding = signature().bind(1, &["c"]).unwrap();
+
+        assert_eq!(
+            binding.slots,
+            vec![
+                ArgSource::Positional(0),
+                ArgSource::Default,
+                ArgSource::Named(0)
+            ]
+        );
+        assert!(binding.rest.is_empty());
+    }
+
+    #[test]
+    fn test_bind_rest() {
+        let sig = Signature::new(&["a"]).with_rest("xs");
+        let binding = sig.bind(4, &[]).unwrap();
+
+        assert_eq!(binding.slots, vec![ArgSource::Positional(0)]);
+        assert_eq!(binding.rest, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        let sig = signature();
+
+        assert_eq!(
+            sig.bind(4, &[]).unwrap_err(),
+            "Expected 1 to 3 arguments but got 4."
+        );
+        assert_eq!(
+            Signature::new(&["a", "b"]).bind(3, &[]).unwrap_err(),
+            "Expected 2 arguments but got 3."
+        );
+        assert_eq!(
+            sig.bind(0, &["b"]).unwrap_err(),
+            "Missing argument for 'a'."
+        );
+        assert_eq!(sig.bind(1, &["d"]).unwrap_err(), "Unknown parameter 'd'.");
+        assert_eq!(
+            sig.bind(2, &["b"]).unwrap_err(),
+            "Argument 'b' given more than once."
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fs, io, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(pub usize);
+
+// 파일 안에서의 위치 (문자 단위 offset, end는 포함하지 않음)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+pub struct SourceFile {
+    pub id: FileId,
+    pub name: String,
+    pub source: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(id: FileId, name: String, raw: &str) -> Self {
+        let source = normalize(raw);
+        let line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+
+        Self {
+            id,
+            name,
+            source,
+            line_starts,
+        }
+    }
+
+    // offset -> (line, column), 둘 다 1부터 시작
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        (line + 1, offset - self.line_starts[line] + 1)
+    }
+}
+
+// BOM 제거, shebang 줄 제거, CRLF -> LF
+fn normalize(raw: &str) -> String {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+
+    // shebang은 지우되 줄바꿈은 남겨서 줄 번호가 밀리지 않게 한다
+    let text = if text.starts_with("#!") {
+        match text.find('\n') {
+            Some(i) => &text[i..],
+            None => "",
+        }
+    } else {
+        text
+    };
+
+    text.replace("\r\n", "\n")
+}
+
+// normalize된 문자 offset -> 원문(raw)의 문자 offset. 끝 offset도 포함한다.
+// CRLF의 '\n'은 '\r' 자리로 간다 (줄 끝에 넣는 글자가 CR 앞에 오도록)
+pub fn raw_offsets(raw: &str) -> Vec<usize> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut i = usize::from(chars.first() == Some(&'\u{feff}'));
+    if chars[i..].starts_with(&['#', '!']) {
+        while i < chars.len() && chars[i] != '\n' {
+            i += 1;
+        }
+    }
+
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    while i < chars.len() {
+        offsets.push(i);
+        i += if chars[i..].starts_with(&['\r', '\n']) {
+            2
+        } else {
+            1
+        };
+    }
+    offsets.push(chars.len());
+    offsets
+}
+
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    pub fn load(&mut self, path: &str) -> io::Result<FileId> {
+        let bytes = fs::read(Path::new(path))?;
+        let contents = String::from_utf8(bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(self.add(path, &contents))
+    }
+
+    pub fn add(&mut self, name: &str, contents: &str) -> FileId {
+        let id = FileId(self.files.len());
+        self.files
+            .push(SourceFile::new(id, name.to_string(), contents));
+        id
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    // 진단 메시지용 "name:line:col"
+    pub fn location(&self, id: FileId, span: Span) -> String {
+        let file = self.get(id);
+        let (line, col) = file.line_col(span.start);
+        format!("{}:{}:{}", file.name, line, col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_bom() {
+        let mut map = SourceMap::new();
+        let id = map.add("a.lox", "\u{feff}var x;");
+
+        assert_eq!(map.get(id).source, "var x;");
+    }
+
+    #[test]
+    fn test_strip_shebang_keeps_line() {
+        let mut map = SourceMap::new();
+        let id = map.add("a.lox", "#!/usr/bin/env jlox\nprint 1;");
+
+        assert_eq!(map.get(id).source, "\nprint 1;");
+        assert_eq!(map.get(id).line_col(1), (2, 1));
+    }
+
+    #[test]
+    fn test_crlf_line_counting() {
+        let mut map = SourceMap::new();
+        let id = map.add("a.lox", "a\r\nb\r\nc");
+        let file = map.get(id);
+
+        assert_eq!(file.source, "a\nb\nc");
+        assert_eq!(file.line_col(4), (3, 1));
+    }
+
+    #[test]
+    fn test_multiple_files() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.lox", "1;");
+        let b = map.add("b.lox", "\n\n  2;");
+
+        assert_ne!(a, b);
+        assert_eq!(map.location(a, Span::new(0, 1)), "a.lox:1:1");
+        assert_eq!(map.location(b, Span::new(4, 5)), "b.lox:3:3");
+    }
+
+    #[test]
+    fn test_raw_offsets() {
+        let raw = "\u{feff}#!jlox\r\na\r\nb";
+        let source = normalize(raw);
+        let offsets = raw_offsets(raw);
+        let raw_chars: Vec<char> = raw.chars().collect();
+
+        assert_eq!(source, "\na\nb");
+        assert_eq!(offsets, [8, 9, 10, 12, 13]);
+        assert_eq!(raw_chars[offsets[2]], '\r');
+        assert_eq!(raw_chars[offsets[3]], 'b');
+    }
+}
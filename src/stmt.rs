@@ -0,0 +1,156 @@
+// Auto-generated by tools/generate_ast.rs
+// Do not edit manually
+
+use crate::expr::Expr;
+use crate::pattern::Pattern;
+use crate::token::Token;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Block(Block),
+    Break(Break),
+    Class(Class),
+    Continue(Continue),
+    Export(Export),
+    Expression(Expression),
+    Function(Function),
+    If(If),
+    Import(Import),
+    Match(Match),
+    Print(Print),
+    Return(Return),
+    Throw(Throw),
+    Trait(Trait),
+    Try(Try),
+    Var(Var),
+    VarPattern(VarPattern),
+    While(While),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Break {
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    pub name: Token,
+    pub superclass: Option<Expr>,
+    pub traits: Vec<Expr>,
+    pub methods: Vec<Function>,
+    pub class_methods: Vec<Function>,
+    pub getters: Vec<Function>,
+    pub static_fields: Vec<Token>,
+    pub static_values: Vec<Expr>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continue {
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub keyword: Token,
+    pub declaration: Box<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub expression: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub defaults: Vec<Option<Expr>>,
+    pub rest: Option<Token>,
+    pub body: Vec<Stmt>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct If {
+    pub condition: Expr,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub keyword: Token,
+    pub path: Token,
+    pub alias: Token,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub keyword: Token,
+    pub value: Expr,
+    pub patterns: Vec<Pattern>,
+    pub guards: Vec<Option<Expr>>,
+    pub bodies: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Print {
+    pub expression: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trait {
+    pub name: Token,
+    pub methods: Vec<Function>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Try {
+    pub keyword: Token,
+    pub body: Vec<Stmt>,
+    pub catch_name: Option<Token>,
+    pub catch_body: Option<Vec<Stmt>>,
+    pub finally_body: Option<Vec<Stmt>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Var {
+    pub name: Token,
+    pub initializer: Option<Expr>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarPattern {
+    pub keyword: Token,
+    pub pattern: Pattern,
+    pub initializer: Expr,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct While {
+    pub condition: Expr,
+    pub body: Box<Stmt>,
+    pub increment: Option<Expr>,
+}
+
@@ -1,6 +1,9 @@
 use std::fmt;
 
-use crate::token_type::TokenType;
+use crate::{
+    source_map::{FileId, Span},
+    token_type::TokenType,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Token {
@@ -8,6 +11,8 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<String>,
     pub line: i32,
+    pub file: FileId,
+    pub span: Span,
 }
 
 impl Token {
@@ -17,6 +22,8 @@ impl Token {
             lexeme,
             literal: None,
             line,
+            file: FileId::default(),
+            span: Span::default(),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TokenType {
     // 단일 문자 토큰
@@ -5,15 +7,39 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    DotDotDot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    Question,
+    Colon,
 
     // 문자 1개 또는 2개 짜리 토큰
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    PlusPlus,
+    MinusMinus,
+    StarStar,
+    TildeSlash,
+    LessLess,
+    GreaterGreater,
+    FatArrow,
+    QuestionDot,
+    QuestionQuestion,
     Bang,
     BangEqual,
     Equal,
@@ -28,23 +54,123 @@ pub enum TokenType {
     String,
     Number,
 
+    // 문서 주석 (///)
+    DocComment,
+
     // 키워드
     And,
+    As,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
+    Export,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
+    Trait,
     True,
+    Try,
     Var,
     While,
+    With,
 
     Eof,
 }
+
+// 에러 메시지에 쓰는 이름
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            TokenType::LeftParen => "`(`",
+            TokenType::RightParen => "`)`",
+            TokenType::LeftBrace => "`{`",
+            TokenType::RightBrace => "`}`",
+            TokenType::LeftBracket => "`[`",
+            TokenType::RightBracket => "`]`",
+            TokenType::Comma => "`,`",
+            TokenType::Dot => "`.`",
+            TokenType::DotDotDot => "`...`",
+            TokenType::Minus => "`-`",
+            TokenType::Plus => "`+`",
+            TokenType::Semicolon => "`;`",
+            TokenType::Slash => "`/`",
+            TokenType::Star => "`*`",
+            TokenType::Percent => "`%`",
+            TokenType::Ampersand => "`&`",
+            TokenType::Pipe => "`|`",
+            TokenType::Caret => "`^`",
+            TokenType::Tilde => "`~`",
+            TokenType::Question => "`?`",
+            TokenType::QuestionDot => "`?.`",
+            TokenType::QuestionQuestion => "`??`",
+            TokenType::Colon => "`:`",
+            TokenType::PlusEqual => "`+=`",
+            TokenType::MinusEqual => "`-=`",
+            TokenType::StarEqual => "`*=`",
+            TokenType::SlashEqual => "`/=`",
+            TokenType::PercentEqual => "`%=`",
+            TokenType::PlusPlus => "`++`",
+            TokenType::MinusMinus => "`--`",
+            TokenType::StarStar => "`**`",
+            TokenType::TildeSlash => "`~/`",
+            TokenType::LessLess => "`<<`",
+            TokenType::GreaterGreater => "`>>`",
+            TokenType::FatArrow => "`=>`",
+            TokenType::Bang => "`!`",
+            TokenType::BangEqual => "`!=`",
+            TokenType::Equal => "`=`",
+            TokenType::EqualEqual => "`==`",
+            TokenType::Greater => "`>`",
+            TokenType::GreaterEqual => "`>=`",
+            TokenType::Less => "`<`",
+            TokenType::LessEqual => "`<=`",
+            TokenType::Identifier => "identifier",
+            TokenType::String => "string",
+            TokenType::Number => "number",
+            TokenType::DocComment => "doc comment",
+            TokenType::And => "`and`",
+            TokenType::As => "`as`",
+            TokenType::Break => "`break`",
+            TokenType::Catch => "`catch`",
+            TokenType::Class => "`class`",
+            TokenType::Continue => "`continue`",
+            TokenType::Else => "`else`",
+            TokenType::Export => "`export`",
+            TokenType::False => "`false`",
+            TokenType::Finally => "`finally`",
+            TokenType::Fun => "`fun`",
+            TokenType::For => "`for`",
+            TokenType::If => "`if`",
+            TokenType::Import => "`import`",
+            TokenType::Match => "`match`",
+            TokenType::Nil => "`nil`",
+            TokenType::Or => "`or`",
+            TokenType::Print => "`print`",
+            TokenType::Return => "`return`",
+            TokenType::Super => "`super`",
+            TokenType::This => "`this`",
+            TokenType::Throw => "`throw`",
+            TokenType::Trait => "`trait`",
+            TokenType::True => "`true`",
+            TokenType::Try => "`try`",
+            TokenType::Var => "`var`",
+            TokenType::While => "`while`",
+            TokenType::With => "`with`",
+            TokenType::Eof => "end of file",
+        };
+        write!(f, "{}", name)
+    }
+}
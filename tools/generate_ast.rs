@@ -16,37 +16,119 @@ fn main() {
     if let Err(e) = define_ast(
         output_dir,
         "Expr",
+        &["crate::pattern::Pattern", "crate::stmt::Stmt", "crate::token::Token"],
         &[
+            "Assign   : Token name, Box<Expr> value",
+            "AssignPattern : Pattern pattern, Token equals, Box<Expr> value",
             "Binary   : Box<Expr> left, Token operator, Box<Expr> right",
+            "Coalesce : Box<Expr> left, Token operator, Box<Expr> right",
+            "Call     : Box<Expr> callee, Token paren, Vec<Expr> arguments, Vec<Option<Token>> names",
+            "Comma    : Box<Expr> left, Box<Expr> right",
+            "CompoundAssign : Box<Expr> target, Token operator, Box<Expr> value",
+            "Conditional : Box<Expr> condition, Box<Expr> then_branch, Box<Expr> else_branch",
+            "Get      : Box<Expr> object, Token name",
             "Grouping : Box<Expr> expression",
+            "Index    : Box<Expr> object, Token bracket, Box<Expr> index",
+            "Lambda   : Token keyword, Vec<Token> params, Vec<Option<Expr>> defaults, Option<Token> rest, Vec<Stmt> body",
+            "List     : Token bracket, Vec<Expr> elements",
             "Literal  : LiteralValue value",
+            "Logical  : Box<Expr> left, Token operator, Box<Expr> right",
+            "Map      : Token brace, Vec<Expr> keys, Vec<Expr> values",
+            "OptionalChain : Box<Expr> expression",
+            "OptionalGet : Box<Expr> object, Token name",
+            "Set      : Box<Expr> object, Token name, Box<Expr> value",
+            "SetIndex : Box<Expr> object, Token bracket, Box<Expr> index, Box<Expr> value",
+            "Super    : Token keyword, Token method",
+            "This     : Token keyword",
             "Unary    : Token operator, Box<Expr> right",
+            "Update   : Box<Expr> target, Token operator, bool prefix",
+            "Variable : Token name",
         ],
     ) {
         eprintln!("Error generating Expr: {}", e);
         std::process::exit(1);
     }
 
+    // Stmt 타입 정의 (doc은 선언 앞의 /// 주석, defaults는 params와 같은 길이)
+    if let Err(e) = define_ast(
+        output_dir,
+        "Stmt",
+        &["crate::expr::Expr", "crate::pattern::Pattern", "crate::token::Token"],
+        &[
+            "Block      : Vec<Stmt> statements",
+            "Break      : Token keyword",
+            "Class      : Token name, Option<Expr> superclass, Vec<Expr> traits, Vec<Function> methods, Vec<Function> class_methods, Vec<Function> getters, Vec<Token> static_fields, Vec<Expr> static_values, Option<String> doc",
+            "Continue   : Token keyword",
+            "Export     : Token keyword, Box<Stmt> declaration",
+            "Expression : Expr expression",
+            "Function   : Token name, Vec<Token> params, Vec<Option<Expr>> defaults, Option<Token> rest, Vec<Stmt> body, Option<String> doc",
+            "If         : Expr condition, Box<Stmt> then_branch, Option<Box<Stmt>> else_branch",
+            "Import     : Token keyword, Token path, Token alias",
+            "Match      : Token keyword, Expr value, Vec<Pattern> patterns, Vec<Option<Expr>> guards, Vec<Stmt> bodies",
+            "Print      : Expr expression",
+            "Return     : Token keyword, Option<Expr> value",
+            "Throw      : Token keyword, Expr value",
+            "Trait      : Token name, Vec<Function> methods, Option<String> doc",
+            "Try        : Token keyword, Vec<Stmt> body, Option<Token> catch_name, Option<Vec<Stmt>> catch_body, Option<Vec<Stmt>> finally_body",
+            "Var        : Token name, Option<Expr> initializer, Option<String> doc",
+            "VarPattern : Token keyword, Pattern pattern, Expr initializer, Option<String> doc",
+            "While      : Expr condition, Box<Stmt> body, Option<Expr> increment",
+        ],
+    ) {
+        eprintln!("Error generating Stmt: {}", e);
+        std::process::exit(1);
+    }
+
+    // match 문의 패턴
+    if let Err(e) = define_ast(
+        output_dir,
+        "Pattern",
+        &["crate::expr::LiteralValue", "crate::token::Token"],
+        &[
+            "Alternation : Vec<Pattern> alternatives",
+            "Binding     : Token name",
+            "Instance    : Token class_name, Vec<Token> fields, Vec<Pattern> patterns",
+            "List        : Token bracket, Vec<Pattern> elements, Option<Token> rest",
+            "Literal     : Token token, LiteralValue value",
+            "Object      : Token brace, Vec<Token> fields, Vec<Pattern> patterns",
+            "Wildcard    : Token token",
+        ],
+    ) {
+        eprintln!("Error generating Pattern: {}", e);
+        std::process::exit(1);
+    }
+
     println!("Successfully generated AST files in '{}'", output_dir);
 }
 
-fn define_ast(output_dir: &str, base_name: &str, types: &[&str]) -> io::Result<()> {
+fn define_ast(
+    output_dir: &str,
+    base_name: &str,
+    imports: &[&str],
+    types: &[&str],
+) -> io::Result<()> {
     let path = format!("{}/{}.rs", output_dir, base_name.to_lowercase());
     let mut file = File::create(&path)?;
 
     // 파일 헤더 및 imports
     writeln!(file, "// Auto-generated by tools/generate_ast.rs")?;
     writeln!(file, "// Do not edit manually\n")?;
-    writeln!(file, "use crate::token::Token;\n")?;
+    for import in imports {
+        writeln!(file, "use {};", import)?;
+    }
+    writeln!(file)?;
 
     // LiteralValue enum 정의 (Option 대신 명시적 타입 사용)
-    writeln!(file, "#[derive(Debug, Clone, PartialEq)]")?;
-    writeln!(file, "pub enum LiteralValue {{")?;
-    writeln!(file, "    Number(f64),")?;
-    writeln!(file, "    String(String),")?;
-    writeln!(file, "    Boolean(bool),")?;
-    writeln!(file, "    Nil,")?;
-    writeln!(file, "}}\n")?;
+    if base_name == "Expr" {
+        writeln!(file, "#[derive(Debug, Clone, PartialEq)]")?;
+        writeln!(file, "pub enum LiteralValue {{")?;
+        writeln!(file, "    Number(f64),")?;
+        writeln!(file, "    Integer(i64),")?;
+        writeln!(file, "    String(String),")?;
+        writeln!(file, "    Boolean(bool),")?;
+        writeln!(file, "    Nil,")?;
+        writeln!(file, "}}\n")?;
+    }
 
     // Base Enum 생성
     write_base_enum(&mut file, base_name, types)?;